@@ -0,0 +1,229 @@
+use crate::{
+    config::{Arbitration, Settings},
+    input::Reading,
+};
+
+// how far a stick must move to count as activity. the jitter of a drifting stick stays below it
+const STICK_ACTIVITY: f32 = 0.1;
+
+/// The devices that make motion inputs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Device {
+    Keyboard,
+    Gamepad,
+}
+
+/// Decides what the keyboard and the gamepad feed to the input buffer each frame
+pub struct Arbiter {
+    // the reading of each device when it's active last time and how many frames it has been idle since then
+    activities: [(Reading, u16); 2],
+    winner: Device,
+}
+
+impl Default for Arbiter {
+    fn default() -> Self {
+        Arbiter {
+            activities: [(Reading::default(), u16::MAX); 2],
+            winner: Device::Keyboard,
+        }
+    }
+}
+
+impl Arbiter {
+    /// The device that makes motion inputs under `last-active`
+    pub fn winner(&self) -> Device {
+        self.winner
+    }
+
+    pub fn arbitrate(&mut self, settings: &Settings, keyboard: Reading, gamepad: Reading) -> Reading {
+        let mut moved = [false; 2];
+        for (device, reading) in [(Device::Keyboard, keyboard), (Device::Gamepad, gamepad)] {
+            let (last, idle) = &mut self.activities[device as usize];
+            if is_activity(last, &reading) {
+                *last = reading;
+                *idle = 0;
+                moved[device as usize] = true;
+            } else {
+                *idle = idle.saturating_add(1);
+            }
+        }
+        // the winner stays when both devices move in the same frame
+        match moved {
+            [true, false] => self.winner = Device::Keyboard,
+            [false, true] => self.winner = Device::Gamepad,
+            _ => (),
+        }
+
+        match settings.device_arbitration {
+            Arbitration::LastActive => match self.winner {
+                Device::Keyboard => keyboard,
+                Device::Gamepad => gamepad,
+            },
+            Arbitration::Merged => {
+                // idle devices are left out so that a drifting stick or a stuck key doesn't get in the way
+                let timeouts = [
+                    settings.keyboard_activity_timeout.as_actual(),
+                    settings.gamepad_activity_timeout.as_actual(),
+                ];
+                let active = |device: Device| self.activities[device as usize].1 < timeouts[device as usize];
+                let keyboard = if active(Device::Keyboard) {
+                    keyboard
+                } else {
+                    Reading::default()
+                };
+                let gamepad = if active(Device::Gamepad) {
+                    gamepad
+                } else {
+                    Reading::default()
+                };
+                // a key held on both devices is pressed when it's pressed first
+                let pressed_at = [0, 1, 2, 3].map(|i| {
+                    let readings = [keyboard, gamepad].into_iter().filter(|it| it.keys[i]);
+                    readings.filter_map(|it| it.pressed_at[i]).min()
+                });
+                Reading {
+                    keys: [0, 1, 2, 3].map(|i| keyboard.keys[i] || gamepad.keys[i]),
+                    pressed_at,
+                    stick: gamepad.stick,
+                }
+            }
+            Arbitration::Keyboard => keyboard,
+            Arbitration::Gamepad => gamepad,
+        }
+    }
+}
+
+// any change of the keys counts but the sticks must move far enough
+fn is_activity(last: &Reading, reading: &Reading) -> bool {
+    if last.keys != reading.keys {
+        return true;
+    }
+    match (last.stick, reading.stick) {
+        (None, None) => false,
+        (Some((last, last_x, last_y)), Some((stick, x, y))) if last == stick => {
+            f32::max((x - last_x).abs(), (y - last_y).abs()) >= STICK_ACTIVITY
+        }
+        (Some((_, x, y)), None) | (None, Some((_, x, y))) => f32::max(x.abs(), y.abs()) >= STICK_ACTIVITY,
+        (Some(_), Some(_)) => true,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{
+        arbiter::{Arbiter, Device},
+        config::{Arbitration, Settings},
+        frame::Frames,
+        input::{Input::*, InputBuffer, Inputs, Reading, Stick},
+    };
+
+    const NONE: [bool; 4] = [false; 4];
+    const UP: [bool; 4] = [true, false, false, false];
+    const RIGHT: [bool; 4] = [false, true, false, false];
+    const DOWN: [bool; 4] = [false, false, true, false];
+
+    fn keys(keys: [bool; 4]) -> Reading {
+        Reading {
+            keys,
+            ..Reading::default()
+        }
+    }
+
+    fn stick(x: f32, y: f32) -> Reading {
+        Reading {
+            stick: Some((Stick::Left, x, y)),
+            ..Reading::default()
+        }
+    }
+
+    // feeds the frames through the arbiter into a fresh input buffer
+    fn run(settings: Settings, frames: &[(Reading, Reading)]) -> (Arbiter, Inputs) {
+        let mut arbiter = Arbiter::default();
+        let mut buffer = InputBuffer::new(settings, 8);
+        let mut inputs = Inputs::new();
+        for (keyboard, gamepad) in frames.iter().copied() {
+            inputs = buffer.update(arbiter.arbitrate(&settings, keyboard, gamepad));
+        }
+        (arbiter, inputs)
+    }
+
+    fn settings(arbitration: Arbitration) -> Settings {
+        Settings {
+            device_arbitration: arbitration,
+            keyboard_activity_timeout: Frames::standard(5),
+            gamepad_activity_timeout: Frames::standard(5),
+            ..Settings::default()
+        }
+    }
+
+    #[test]
+    fn test_last_active() {
+        let settings = settings(Arbitration::LastActive);
+        // a drifting stick doesn't take over the keyboard
+        let drift = [stick(0.05, 0.12), stick(0.07, 0.15), stick(0.04, 0.11)];
+        let mut frames = vec![(keys(NONE), drift[0])];
+        for (i, keyboard) in [UP, NONE, UP, NONE, DOWN].into_iter().enumerate() {
+            frames.push((keys(keyboard), drift[i % 3]));
+        }
+        let (arbiter, inputs) = run(settings, &frames);
+        assert_eq!(arbiter.winner(), Device::Keyboard);
+        assert_eq!(inputs, Inputs::from([Up, Up, Down]));
+
+        // the stick takes over once it moves and the keyboard takes it back once its keys change
+        let frames = [
+            (keys(UP), stick(0.0, 0.0)),
+            (keys(UP), stick(1.0, 0.0)),
+            (keys(UP), stick(0.0, 0.0)),
+            (keys(UP), stick(1.0, 0.0)),
+            (keys(NONE), stick(1.0, 0.0)),
+        ];
+        let (arbiter, inputs) = run(settings, &frames);
+        assert_eq!(arbiter.winner(), Device::Keyboard);
+        assert_eq!(inputs, Inputs::from([Up, Right, Right]));
+        let (arbiter, _) = run(settings, &frames[..4]);
+        assert_eq!(arbiter.winner(), Device::Gamepad);
+    }
+
+    #[test]
+    fn test_merged() {
+        let settings = settings(Arbitration::Merged);
+        // both devices make inputs in the same sequence
+        let frames = [
+            (keys(DOWN), stick(0.0, 0.0)),
+            (keys(NONE), stick(0.0, 0.0)),
+            (keys(NONE), stick(1.0, 0.0)),
+        ];
+        let (_, inputs) = run(settings, &frames);
+        assert_eq!(inputs, Inputs::from([Down, Right]));
+
+        // the D-pad and the keys are merged
+        let (_, inputs) = run(settings, &[(keys(UP), keys(RIGHT)), (keys(NONE), keys(NONE))]);
+        assert_eq!(inputs, Inputs::from([Up, Right]));
+
+        // a stick left off the center is left out once it's idle for the timeout
+        let mut frames = vec![(keys(NONE), stick(0.0, 0.95))];
+        frames.extend([(keys(NONE), stick(0.0, 0.95)); 5]);
+        frames.push((keys(RIGHT), stick(0.0, 0.95)));
+        frames.push((keys(NONE), stick(0.0, 0.95)));
+        frames.push((keys(RIGHT), stick(0.0, 0.95)));
+        let (_, inputs) = run(settings, &frames);
+        assert_eq!(inputs, Inputs::from([Up, Right, Right]));
+        // and comes back once it moves
+        frames.push((keys(NONE), stick(0.0, -0.95)));
+        let (_, inputs) = run(settings, &frames);
+        assert_eq!(inputs, Inputs::from([Up, Right, Right, Down]));
+    }
+
+    #[test]
+    fn test_fixed_device() {
+        let frames = [
+            (keys(UP), stick(1.0, 0.0)),
+            (keys(NONE), stick(0.0, 0.0)),
+            (keys(UP), stick(1.0, 0.0)),
+        ];
+        let (_, inputs) = run(settings(Arbitration::Keyboard), &frames);
+        assert_eq!(inputs, Inputs::from([Up, Up]));
+        let (_, inputs) = run(settings(Arbitration::Gamepad), &frames);
+        assert_eq!(inputs, Inputs::from([Right, Right]));
+    }
+}
@@ -0,0 +1,234 @@
+//! Checks a config file without booting the game.
+//!
+//! Usage: `check_config [path/to/battle_instinct.cfg]`
+//!
+//! Every problem in the config is printed to stderr and every effective binding of each profile is printed
+//! to stdout, including the alternative forms generated for fault tolerance. Exits with a non-zero code
+//! when the config can not be read or contains errors.
+
+use std::{collections::HashMap, env, fs, process::ExitCode};
+
+use sekiro_battle_instinct::{
+    config::{Binding, Calibration, Config, Profile, Severity, UID, possible_altenrnatives},
+    input::{Inputs, InputsTrie, Notation},
+    key::{Button, Key, Trigger},
+    skill::describe,
+};
+
+const DEFAULT_PATH: &str = "battle_instinct.cfg";
+
+fn main() -> ExitCode {
+    let path = env::args().nth(1).unwrap_or_else(|| DEFAULT_PATH.to_string());
+    let src = match fs::read_to_string(&path) {
+        Ok(src) => src,
+        Err(e) => {
+            eprintln!("{path}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let (config, diagnostics) = Config::parse(src);
+    for diagnostic in diagnostics.iter() {
+        eprintln!(
+            "{path}:{}:{}: {}: {}",
+            diagnostic.line, diagnostic.column, diagnostic.severity, diagnostic.message
+        );
+    }
+
+    print_profile(&config.base, config.notation);
+    for profile in config.profiles.iter() {
+        println!("Profile `{}`", profile.name);
+        println!();
+        print_profile(profile, config.notation);
+    }
+    println!("Fallbacks");
+    let mut arts = config.fallbacks.keys().copied().collect::<Vec<_>>();
+    arts.sort();
+    for art in arts {
+        let chain = config
+            .fallback_chain(art)
+            .map(|it| describe(it).to_string())
+            .collect::<Vec<_>>();
+        println!("  {}", chain.join(" > "));
+    }
+    println!();
+    println!("Combat art workarounds");
+    let mut arts = config.art_settings.iter().collect::<Vec<_>>();
+    arts.sort_by_key(|(art, _)| **art);
+    for (art, settings) in arts {
+        println!(
+            "  {}  sheathed={}  swapout_cooldown={}  block_injection_duration={}  attack_suppression={}",
+            describe(*art),
+            if settings.sheathed { "on" } else { "off" },
+            settings.swapout_cooldown.as_standard(),
+            settings.block_injection_duration,
+            if settings.attack_suppression { "on" } else { "off" },
+        );
+    }
+    println!();
+    println!("Gamepads");
+    let mut gamepads = config
+        .gamepads
+        .gamepads
+        .iter()
+        .map(|(uuid, it)| (uuid.map(|it| format!("{it:02x}")).concat(), it))
+        .collect::<Vec<_>>();
+    gamepads.sort_by(|(a, _), (b, _)| a.cmp(b));
+    for (gamepad, settings) in std::iter::once(("*".to_string(), &config.gamepads.default)).chain(gamepads) {
+        for (stick, calibration) in ["left", "right"].iter().zip(&settings.sticks) {
+            println!("  {gamepad}  {stick:<7}  {}", describe_calibration(calibration));
+        }
+        let paddles = settings
+            .paddles
+            .iter()
+            .enumerate()
+            .filter_map(|(i, code)| Some((i + 1, (*code)?)));
+        let paddles = paddles.map(|(n, code)| format!("paddle{n}={code}")).collect::<Vec<_>>();
+        if !paddles.is_empty() {
+            println!("  {gamepad}  paddles  {}", paddles.join("  "));
+        }
+    }
+    println!();
+    println!("Keys");
+    let keys = &config.keys;
+    for (slot, keys) in [
+        ("↑", &keys.up),
+        ("→", &keys.right),
+        ("↓", &keys.down),
+        ("←", &keys.left),
+    ] {
+        let keys = keys.iter().map(Key::to_string).collect::<Vec<_>>().join(", ");
+        println!("  {slot:<6} {keys}");
+    }
+    for (slot, triggers) in [("M4", &keys.x1), ("M5", &keys.x2)] {
+        let triggers = triggers.iter().map(Trigger::to_string).collect::<Vec<_>>().join(", ");
+        println!("  {slot:<6} {triggers}");
+    }
+    for (slot, triggers) in [("Profile", &keys.profile), ("Reset", &keys.reset)] {
+        if !triggers.is_empty() {
+            let triggers = triggers.iter().map(Trigger::to_string).collect::<Vec<_>>().join(", ");
+            println!("  {slot:<6} {triggers}");
+        }
+    }
+
+    if diagnostics.iter().any(|it| it.severity == Severity::Error) {
+        ExitCode::FAILURE
+    } else {
+        ExitCode::SUCCESS
+    }
+}
+
+fn print_profile(profile: &Profile, notation: Notation) {
+    println!("Combat arts");
+    print_trie(profile, notation, &profile.arts, |inputs, arts| {
+        describe_arts(profile, Binding::Motion(inputs), arts)
+    });
+    for (binding, name, arts) in [
+        (Binding::X1, "M4", &profile.arts_on_x1),
+        (Binding::X2, "M5", &profile.arts_on_x2),
+    ] {
+        if !arts.is_empty() {
+            println!("  {name:<6} {}", describe_arts(profile, binding, arts));
+        }
+    }
+    for (button, arts) in sorted_by_name(&profile.arts_on_buttons) {
+        println!(
+            "  {button:<6} {}",
+            describe_arts(profile, Binding::Button(button), arts)
+        );
+    }
+    println!();
+    println!("Prosthetic tools");
+    print_trie(profile, notation, &profile.tools, |inputs, tools| {
+        describe_tools(profile, Binding::Motion(inputs), tools)
+    });
+    for (binding, name, tools) in [
+        (Binding::Block, "⛉", &profile.tools_for_block),
+        (Binding::X1, "M4", &profile.tools_on_x1),
+        (Binding::X2, "M5", &profile.tools_on_x2),
+    ] {
+        if !tools.is_empty() {
+            println!("  {name:<6} {}", describe_tools(profile, binding, tools));
+        }
+    }
+    for (button, tools) in sorted_by_name(&profile.tools_on_buttons) {
+        println!(
+            "  {button:<6} {}",
+            describe_tools(profile, Binding::Button(button), tools)
+        );
+    }
+    println!();
+}
+
+fn print_trie<T: PartialEq>(
+    profile: &Profile,
+    notation: Notation,
+    trie: &InputsTrie<T>,
+    display: impl Fn(Inputs, &T) -> String,
+) {
+    let mut entries = trie.iter().collect::<Vec<_>>();
+    entries.sort_by_key(|(inputs, _)| (inputs.len(), inputs.perfect_hash()));
+    for (inputs, value) in entries.iter().copied() {
+        let text = display(inputs, value);
+        // an alternative form shares the same skills with the sequence it's derived from
+        let origin = entries.iter().copied().find(|(origin, origin_value)| {
+            let tolerances = profile.tolerances.get(origin).copied().unwrap_or_default();
+            *origin != inputs && *origin_value == value && possible_altenrnatives(*origin, tolerances).contains(&inputs)
+        });
+        let inputs = inputs.display(notation);
+        match origin {
+            Some((origin, _)) => println!("  {inputs:<6} {text}  (alternative of {})", origin.display(notation)),
+            None => println!("  {inputs:<6} {text}"),
+        }
+    }
+}
+
+fn describe_arts(profile: &Profile, binding: Binding, arts: &[UID]) -> String {
+    // the policy only matters when there're multiple combat arts to pick from
+    match profile.art_policies.get(&binding) {
+        Some(policy) if arts.len() > 1 => format!("{}  @{}", join(arts), policy.name()),
+        _ => join(arts),
+    }
+}
+
+fn describe_tools(profile: &Profile, binding: Binding, tools: &[UID]) -> String {
+    // the policy only matters when there're multiple tools to pick from
+    match profile.tool_policies.get(&binding) {
+        Some(policy) if tools.len() > 1 => format!("{}  @{}", join(tools), policy.name()),
+        _ => join(tools),
+    }
+}
+
+fn describe_calibration(calibration: &Calibration) -> String {
+    let switch = |on| if on { "on" } else { "off" };
+    format!(
+        "deadzone={}  inner_deadzone={}  outer_deadzone={}  invert_x={}  invert_y={}  scale_x={}  scale_y={}  \
+        offset_x={}  offset_y={}  drift_compensation={}",
+        calibration.deadzone.name(),
+        calibration.inner_deadzone,
+        calibration.outer_deadzone,
+        switch(calibration.invert[0]),
+        switch(calibration.invert[1]),
+        calibration.scale[0],
+        calibration.scale[1],
+        calibration.offset.0,
+        calibration.offset.1,
+        switch(calibration.drift_compensation),
+    )
+}
+
+fn sorted_by_name(skills: &HashMap<Button, Vec<UID>>) -> Vec<(Button, &[UID])> {
+    let mut skills = skills
+        .iter()
+        .map(|(button, it)| (*button, it.as_slice()))
+        .collect::<Vec<_>>();
+    skills.sort_by_key(|(button, _)| button.to_string());
+    skills
+}
+
+fn join(uids: &[UID]) -> String {
+    uids.iter()
+        .map(|it| describe(*it).to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
@@ -0,0 +1,48 @@
+//! Rewrites a config file canonically, with the columns re-aligned and the glyphs normalized.
+//! Comments are kept in place.
+//!
+//! Usage: `format_config [--check] [path/to/battle_instinct.cfg]`
+//!
+//! With `--check`, the file is left untouched and the exit code tells whether it's already formatted.
+
+use std::{env, fs, process::ExitCode};
+
+use sekiro_battle_instinct::config::Document;
+
+const DEFAULT_PATH: &str = "battle_instinct.cfg";
+
+fn main() -> ExitCode {
+    let mut check = false;
+    let mut path = DEFAULT_PATH.to_string();
+    for arg in env::args().skip(1) {
+        if arg == "--check" {
+            check = true;
+        } else {
+            path = arg;
+        }
+    }
+
+    let src = match fs::read_to_string(&path) {
+        Ok(src) => src,
+        Err(e) => {
+            eprintln!("{path}: {e}");
+            return ExitCode::FAILURE;
+        }
+    };
+    let mut document = Document::parse(&src);
+    document.format();
+    let formatted = document.to_string();
+
+    if formatted == src {
+        return ExitCode::SUCCESS;
+    }
+    if check {
+        eprintln!("{path}: not formatted");
+        return ExitCode::FAILURE;
+    }
+    if let Err(e) = fs::write(&path, formatted) {
+        eprintln!("{path}: {e}");
+        return ExitCode::FAILURE;
+    }
+    ExitCode::SUCCESS
+}
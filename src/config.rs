@@ -1,159 +1,2480 @@
 use std::{
     collections::{HashMap, HashSet},
-    fs, io,
+    fmt, fs, io,
+    ops::RangeInclusive,
     path::Path,
+    str::FromStr,
 };
 
+#[cfg(windows)]
 use widestring::U16CStr;
 
+#[cfg(windows)]
+use crate::game;
 use crate::{
-    core::UID,
-    game,
-    input::{Input::*, Inputs, InputsTrie},
+    frame::Frames,
+    input::{Input, Input::*, Inputs, InputsTrie, Notation, ParseInputsError, Stick},
+    key::{Button, Key, Trigger},
+    skill::{ASHINA_CROSS, ONE_MIND, SAKURA_DANCE, SKILLS, Skill, describe},
 };
 
+/// UIDs are consistent through different save files.
+pub type UID = u32;
+
 const COMBART_ART_UID_MIN: UID = 5000;
 const COMBART_ART_UID_MAX: UID = 10000;
 const PROSTHETIC_TOOL_UID_MIN: UID = 70000;
 const PROSTHETIC_TOOL_UID_MAX: UID = 100000;
 
-#[derive(Debug)]
-pub struct Config {
-    pub arts: InputsTrie<UID>,
-    pub tools: InputsTrie<&'static [UID]>,
-    pub tools_for_block: &'static [UID],
-    pub tools_on_x1: &'static [UID],
-    pub tools_on_x2: &'static [UID],
+// buffer behavior
+const MAX_INTERVAL: Frames = Frames::standard(10);
+const MAX_DELAY: Frames = Frames::standard(10);
+const MAX_DELAY_FOR_SINGLE_INPUT: Frames = Frames::standard(2);
+// joystick ergonomics
+const COMMON_THRESHOLD: f32 = 0.85;
+const ROTATE_THRESHOLD: f32 = 0.9;
+const BOUNCE_THRESHOLD: f32 = 0.4;
+// the right stick springs back past the center after flicks, which must not make bouncing inputs
+const RIGHT_BOUNCE_THRESHOLD: f32 = 0.7;
+// arbitration between the keyboard and the gamepad
+const ACTIVITY_TIMEOUT: Frames = Frames::standard(30);
+// stick calibration
+const INNER_DEADZONE: f32 = 0.0;
+const OUTER_DEADZONE: f32 = 1.0;
+// diagonals
+const DIAGONAL_SECTOR: f32 = 30.0;
+const CHORD_WINDOW: Frames = Frames::standard(3);
+// MOD behavior
+const BLOCK_INJECTION_DURATION: u8 = 10;
+const ATTACK_SUPRESSION_DURATION: u8 = 2;
+const PROSTHETIC_SUPRESSION_DURATION: u8 = 2;
+const PROSTHETIC_ROLLBACK_COUNTDOWN: Frames = Frames::standard(120);
+const PREVIOUS_SLOT_ROLLBACK_COUNTDOWN: Frames = Frames::standard(120);
+const STICKY_ROLLBACK_COUNTDOWN: Frames = Frames::standard(120);
+// workarounds for the animation bugs of combat arts
+const SWAPOUT_COOLDOWN: Frames = Frames::standard(40);
+#[rustfmt::skip]
+const ART_SETTINGS: &[(UID, bool, u16)] = &[
+    // art          sheathed  swap-out cooldown
+    (ASHINA_CROSS,  true,     75),
+    (ONE_MIND,      true,     240),
+    (SAKURA_DANCE,  false,    60),
+];
+
+/// The name of the profile made of the bindings outside of `[profile: NAME]` sections
+pub const BASE_PROFILE: &str = "default";
+
+#[derive(Debug, Default)]
+pub struct Config {
+    /// Bindings outside of any profile, which all the profiles inherit from
+    pub base: Profile,
+    /// Profiles declared with `[profile: NAME]`, in the order they're declared
+    pub profiles: Vec<Profile>,
+    pub keys: Keys,
+    pub settings: Settings,
+    /// The notation the motions are written in, which is used to print them back
+    pub notation: Notation,
+    /// The combat art to try next when a combat art is not owned. There're never cycles
+    pub fallbacks: HashMap<UID, UID>,
+    /// Workarounds for the combat arts that differ from the defaults
+    pub art_settings: HashMap<UID, ArtSettings>,
+    pub gamepads: Gamepads,
+}
+
+/// A complete set of bindings. Only one of the profiles takes effect at a time
+#[derive(Debug, Default)]
+pub struct Profile {
+    pub name: String,
+    /// Combat arts bound to each sequence, one of which is picked by the policy of the sequence
+    pub arts: InputsTrie<Vec<UID>>,
+    pub arts_on_x1: Vec<UID>,
+    pub arts_on_x2: Vec<UID>,
+    pub arts_on_buttons: HashMap<Button, Vec<UID>>,
+    /// How one of the combat arts is picked for each of the bindings above
+    pub art_policies: HashMap<Binding, ArtPolicy>,
+    pub tools: InputsTrie<Vec<UID>>,
+    pub tools_for_block: Vec<UID>,
+    pub tools_on_x1: Vec<UID>,
+    pub tools_on_x2: Vec<UID>,
+    pub tools_on_buttons: HashMap<Button, Vec<UID>>,
+    /// How one of the prosthetic tools is picked for each of the bindings above
+    pub tool_policies: HashMap<Binding, ToolPolicy>,
+    /// Fault tolerance rules that apply to each of the bound sequences
+    pub tolerances: HashMap<Inputs, Tolerances>,
+}
+
+impl Config {
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Config> {
+        let (config, diagnostics) = Config::parse(fs::read_to_string(path)?);
+        for diagnostic in diagnostics {
+            match diagnostic.severity {
+                Severity::Warning => log::warn!("{diagnostic}"),
+                Severity::Error => log::error!("{diagnostic}"),
+            }
+        }
+        Ok(config)
+    }
+
+    /// How many motion inputs the input buffer needs to remember. It's never shorter than 3 so that
+    /// stray inputs before short sequences still flush the buffer as they always did
+    pub fn longest_motion(&self) -> u8 {
+        let arts = self
+            .iter_profiles()
+            .flat_map(|it| it.arts.iter().map(|(inputs, _)| inputs.len()));
+        let tools = self
+            .iter_profiles()
+            .flat_map(|it| it.tools.iter().map(|(inputs, _)| inputs.len()));
+        arts.chain(tools).fold(3, u8::max)
+    }
+
+    /// The base profile and then the declared ones, which is the order they're cycled through
+    pub fn iter_profiles(&self) -> impl Iterator<Item = &Profile> {
+        std::iter::once(&self.base).chain(&self.profiles)
+    }
+
+    /// The profile at the index of `iter_profiles`. Falls back to the base profile when out of range
+    pub fn profile(&self, index: usize) -> &Profile {
+        self.iter_profiles().nth(index).unwrap_or(&self.base)
+    }
+
+    /// Workarounds for the animation bugs of the combat art
+    pub fn art(&self, art: UID) -> ArtSettings {
+        let default = || ArtSettings::new(&self.settings);
+        self.art_settings.get(&art).copied().unwrap_or_else(default)
+    }
+
+    /// The combat art itself and then the ones to try in order when it's not owned
+    pub fn fallback_chain(&self, art: UID) -> impl Iterator<Item = UID> {
+        // the length is limited in case cycles are introduced after parsing
+        let fallbacks = &self.fallbacks;
+        std::iter::successors(Some(art), |art| fallbacks.get(art).copied()).take(fallbacks.len() + 1)
+    }
+
+    /// Parses the config and reports every problem found along the way. Lines with errors are skipped
+    /// while the rest of the config still takes effect.
+    pub fn parse(src: impl AsRef<str>) -> (Config, Vec<Diagnostic>) {
+        let mut parser = Parser {
+            layers: vec![Layer::default()],
+            ..Default::default()
+        };
+        // upgraded combat arts fall back to their basic versions unless specified
+        parser.config.fallbacks = SKILLS
+            .iter()
+            .filter(|it| !it.category.is_prosthetic_tool() && it.family != it.uid)
+            .map(|it| (it.uid, it.family))
+            .collect();
+        for (line, text) in src.as_ref().lines().enumerate() {
+            parser.line = line + 1;
+            parser.parse_line(text);
+        }
+        parser.finish()
+    }
+}
+
+impl<S: AsRef<str>> From<S> for Config {
+    fn from(value: S) -> Config {
+        Config::parse(value).0
+    }
+}
+
+/// Keys that are used as motion inputs and extra buttons on keyboards and mice
+#[derive(Debug, Clone)]
+pub struct Keys {
+    pub up: Vec<Key>,
+    pub right: Vec<Key>,
+    pub down: Vec<Key>,
+    pub left: Vec<Key>,
+    /// Keys and buttons that work as `M4`/`M5` in the bindings
+    pub x1: Vec<Trigger>,
+    pub x2: Vec<Trigger>,
+    /// Keys and buttons that switch to the next profile
+    pub profile: Vec<Trigger>,
+    /// Keys and buttons that bring back the `∅` combat art under `sticky_art`
+    pub reset: Vec<Trigger>,
+}
+
+impl Default for Keys {
+    fn default() -> Self {
+        Keys {
+            up: vec![Key::W],
+            right: vec![Key::D],
+            down: vec![Key::S],
+            left: vec![Key::A],
+            x1: vec![Trigger::Key(Key::XBUTTON1)],
+            x2: vec![Trigger::Key(Key::XBUTTON2)],
+            profile: Vec::new(),
+            reset: Vec::new(),
+        }
+    }
+}
+
+/// Timings and thresholds that govern the input feel
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Settings {
+    /// Max interval between 2 motion inputs for them to form a sequence
+    pub max_interval: Frames,
+    /// How long a sequence lasts after the last motion input
+    pub max_delay: Frames,
+    /// How long a single motion input lasts after it's released
+    pub max_delay_for_single_input: Frames,
+    /// Joystick threshold for repeating the last direction or starting a sequence
+    pub common_threshold: f32,
+    /// Joystick threshold for rotating to an adjacent direction
+    pub rotate_threshold: f32,
+    /// Joystick threshold for bouncing to the opposite direction
+    pub bounce_threshold: f32,
+    /// The parts of gamepads that make motion inputs besides the keys
+    pub gamepad_motion_source: MotionSources,
+    /// The same thresholds as above but for the right stick
+    pub right_common_threshold: f32,
+    pub right_rotate_threshold: f32,
+    pub right_bounce_threshold: f32,
+    /// Which of the keyboard and the gamepad makes motion inputs when both are in use
+    pub device_arbitration: Arbitration,
+    /// How long the keyboard stays active after its motion keys change
+    pub keyboard_activity_timeout: Frames,
+    /// How long the gamepad stays active after its sticks move or its D-pad changes
+    pub gamepad_activity_timeout: Frames,
+    /// Fault tolerance rules that apply unless modified by the bindings
+    pub tolerances: Tolerances,
+    /// Whether the stick and the keys make diagonal inputs. Motions bound without diagonals then also take the
+    /// ones the stick passes through, e.g., `←↖↑↗→` for `←↑→`
+    pub diagonals: bool,
+    /// How wide (in degrees) the sector of each diagonal is on the stick. The cardinal directions take the rest
+    pub diagonal_sector: f32,
+    /// Max interval between 2 keys for them to make a diagonal
+    pub chord_window: Frames,
+    /// What opposite keys held together make
+    pub socd: Socd,
+    /// How many frames BLOCK is injected for block-free combat arts
+    pub block_injection_duration: u8,
+    /// How many frames ATTACK is suppressed after switching combat arts
+    pub attack_suppression_duration: u8,
+    /// How many frames USE_PROSTHETIC is suppressed after switching prosthetic tools
+    pub prosthetic_suppression_duration: u8,
+    /// Whether the last performed motion art replaces the `∅` combat art until the reset key is pressed
+    pub sticky_art: bool,
+    /// What to roll back to after using prosthetic tools
+    pub rollback: Rollback,
+    /// How long it takes to roll back to the default prosthetic tool
+    pub prosthetic_rollback_countdown: Frames,
+    /// How long it takes to roll back to the previous slot under `previous-slot`
+    pub previous_slot_rollback_countdown: Frames,
+    /// How long it takes to roll back to the last used tool under `sticky`
+    pub sticky_rollback_countdown: Frames,
+    /// How one of the combat arts bound to the same sequence is picked unless specified by the bindings
+    pub art_policy: ArtPolicy,
+    /// How one of the prosthetic tools bound to the same input is picked unless specified by the bindings
+    pub tool_policy: ToolPolicy,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            max_interval: MAX_INTERVAL,
+            max_delay: MAX_DELAY,
+            max_delay_for_single_input: MAX_DELAY_FOR_SINGLE_INPUT,
+            common_threshold: COMMON_THRESHOLD,
+            rotate_threshold: ROTATE_THRESHOLD,
+            bounce_threshold: BOUNCE_THRESHOLD,
+            gamepad_motion_source: MotionSources::from([MotionSource::LeftStick]),
+            right_common_threshold: COMMON_THRESHOLD,
+            right_rotate_threshold: ROTATE_THRESHOLD,
+            right_bounce_threshold: RIGHT_BOUNCE_THRESHOLD,
+            device_arbitration: Arbitration::LastActive,
+            keyboard_activity_timeout: ACTIVITY_TIMEOUT,
+            gamepad_activity_timeout: ACTIVITY_TIMEOUT,
+            tolerances: Tolerances::ALL,
+            diagonals: false,
+            diagonal_sector: DIAGONAL_SECTOR,
+            chord_window: CHORD_WINDOW,
+            socd: Socd::Both,
+            block_injection_duration: BLOCK_INJECTION_DURATION,
+            attack_suppression_duration: ATTACK_SUPRESSION_DURATION,
+            prosthetic_suppression_duration: PROSTHETIC_SUPRESSION_DURATION,
+            sticky_art: false,
+            rollback: Rollback::Default,
+            prosthetic_rollback_countdown: PROSTHETIC_ROLLBACK_COUNTDOWN,
+            previous_slot_rollback_countdown: PREVIOUS_SLOT_ROLLBACK_COUNTDOWN,
+            sticky_rollback_countdown: STICKY_ROLLBACK_COUNTDOWN,
+            art_policy: ArtPolicy::FirstOwned,
+            tool_policy: ToolPolicy::EquippedFirst,
+        }
+    }
+}
+
+/// How the MOD works around the animation bugs of a combat art
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ArtSettings {
+    /// Whether BLOCK is held as long as ATTACK is, for the combat arts that sheathe the sword
+    pub sheathed: bool,
+    /// How long switching away from the combat art is postponed after performing it
+    pub swapout_cooldown: Frames,
+    /// How many frames BLOCK is injected when the combat art is performed without BLOCK
+    pub block_injection_duration: u8,
+    /// Whether ATTACK is suppressed for a few frames after switching to the combat art
+    pub attack_suppression: bool,
+}
+
+impl ArtSettings {
+    fn new(settings: &Settings) -> ArtSettings {
+        ArtSettings {
+            sheathed: false,
+            swapout_cooldown: SWAPOUT_COOLDOWN,
+            block_injection_duration: settings.block_injection_duration,
+            attack_suppression: true,
+        }
+    }
+}
+
+impl Settings {
+    /// How long it takes to roll back under the rollback mode. `None` when it never rolls back
+    pub fn rollback_countdown(&self) -> Option<Frames> {
+        match self.rollback {
+            Rollback::Default => Some(self.prosthetic_rollback_countdown),
+            Rollback::PreviousSlot => Some(self.previous_slot_rollback_countdown),
+            Rollback::Never => None,
+            Rollback::Sticky => Some(self.sticky_rollback_countdown),
+        }
+    }
+}
+
+// the names of an enum written in the config, matched ignoring cases and taking `_` for `-`.
+// aliases follow the names, e.g., `LeastRecentlyUsed => "least-recently-used" | "lru"`
+macro_rules! names {
+    ($ty:ident { $($variant:ident => $name:literal $(| $alias:literal)*),+ $(,)? }) => {
+        impl $ty {
+            pub const ALL: [$ty; [$($name),+].len()] = [$($ty::$variant),+];
+
+            pub fn name(self) -> &'static str {
+                match self {
+                    $($ty::$variant => $name,)+
+                }
+            }
+
+            fn from_name(name: &str) -> Option<$ty> {
+                match name.to_ascii_lowercase().replace('_', "-").as_str() {
+                    $($name $(| $alias)* => Some($ty::$variant),)+
+                    _ => None,
+                }
+            }
+
+            /// The names for messages, e.g., `` `a` or `b` `` or `one of a, b and c`
+            pub fn expected() -> String {
+                one_of(&$ty::ALL.map($ty::name))
+            }
+        }
+    };
+}
+
+fn one_of(names: &[&str]) -> String {
+    match names {
+        [a, b] => format!("`{a}` or `{b}`"),
+        names => format!("one of {}", list(names)),
+    }
+}
+
+// `a, b and c`
+fn list(names: &[&str]) -> String {
+    match names {
+        [] => String::new(),
+        [name] => name.to_string(),
+        [names @ .., last] => format!("{} and {last}", names.join(", ")),
+    }
+}
+
+/// What happens some time after using a prosthetic tool
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Rollback {
+    /// Rolls back to the `∅` tools, or to the previous slot when there're none
+    #[default]
+    Default,
+    /// Rolls back to the previous slot even when there're `∅` tools
+    PreviousSlot,
+    /// Stays on the last used tool
+    Never,
+    /// Rolls back to the last used tool, which takes the place of the `∅` tools
+    Sticky,
+}
+
+names!(Rollback {
+    Default => "default",
+    PreviousSlot => "previous-slot",
+    Never => "never",
+    Sticky => "sticky",
+});
+
+/// How one of the combat arts bound to the same sequence is picked
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ArtPolicy {
+    /// The first one owned, so that the same config works for saves at different progress
+    #[default]
+    FirstOwned,
+    /// The one after the last performed, so that the combat arts take turns
+    RoundRobin,
+}
+
+names!(ArtPolicy {
+    FirstOwned => "first-owned",
+    RoundRobin => "round-robin",
+});
+
+/// How one of the prosthetic tools bound to the same input is picked
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ToolPolicy {
+    /// The first one equipped in the slots, or else the first one owned
+    #[default]
+    EquippedFirst,
+    /// The first one owned, no matter what's equipped
+    StrictOrder,
+    /// The one after the last used, so that each use moves on to the next one
+    Cycle,
+    /// The one that has gone unused for the longest time
+    LeastRecentlyUsed,
+}
+
+names!(ToolPolicy {
+    EquippedFirst => "equipped-first",
+    StrictOrder => "strict-order",
+    Cycle => "cycle",
+    LeastRecentlyUsed => "least-recently-used" | "lru",
+});
+
+/// How the keyboard and the gamepad share the input buffer
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arbitration {
+    /// The device with the latest activity makes motion inputs
+    LastActive,
+    /// Both devices make motion inputs as long as they're active
+    Merged,
+    /// Only the keyboard makes motion inputs
+    Keyboard,
+    /// Only the gamepad makes motion inputs
+    Gamepad,
+}
+
+names!(Arbitration {
+    LastActive => "last-active",
+    Merged => "merged",
+    Keyboard => "keyboard",
+    Gamepad => "gamepad",
+});
+
+/// What simultaneous opposite cardinal directions (SOCD), e.g., A and D held together, make. They're ordered by
+/// the times they're pressed, or by frames when there're no timestamps, in which case the ones pressed in the same
+/// frame are pressed together and neither of them wins
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Socd {
+    /// Both directions in the order they're pressed
+    Both,
+    /// The one pressed later, and the other one again once it's released
+    LastInputWins,
+    /// The one pressed earlier, and the other one once it's released
+    FirstInputWins,
+    /// Neither of them, and the one still held once the other one is released
+    Neutral,
+}
+
+names!(Socd {
+    Both => "both",
+    LastInputWins => "last-input-wins" | "last",
+    FirstInputWins => "first-input-wins" | "first",
+    Neutral => "neutral",
+});
+
+/// A part of gamepads that makes motion inputs
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MotionSource {
+    LeftStick,
+    /// Flicking the right stick moves the camera as well
+    RightStick,
+    /// Works like the keys
+    DPad,
+}
+
+names!(MotionSource {
+    LeftStick => "left-stick",
+    RightStick => "right-stick",
+    DPad => "dpad",
+});
+
+/// A set of motion sources
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct MotionSources(u8);
+
+impl MotionSources {
+    pub const NONE: MotionSources = MotionSources(0);
+
+    pub fn contains(self, source: MotionSource) -> bool {
+        self.0 & 1 << source as u8 != 0
+    }
+
+    pub fn insert(&mut self, source: MotionSource) {
+        self.0 |= 1 << source as u8;
+    }
+
+    // `none` or the names joined with `+`, e.g., `left-stick+dpad`
+    fn from_names(names: &str) -> Option<MotionSources> {
+        if names.eq_ignore_ascii_case("none") {
+            return Some(MotionSources::NONE);
+        }
+        let mut sources = MotionSources::NONE;
+        for name in names.split('+') {
+            sources.insert(MotionSource::from_name(name)?);
+        }
+        Some(sources)
+    }
+}
+
+impl<const N: usize> From<[MotionSource; N]> for MotionSources {
+    fn from(array: [MotionSource; N]) -> Self {
+        let mut sources = MotionSources::NONE;
+        for source in array {
+            sources.insert(source);
+        }
+        sources
+    }
+}
+
+// settings of a combat art specified in `[arts]`, which override the defaults one by one
+#[derive(Default, Clone, Copy)]
+struct ArtOverrides {
+    sheathed: Option<bool>,
+    swapout_cooldown: Option<Frames>,
+    block_injection_duration: Option<u8>,
+    attack_suppression: Option<bool>,
+}
+
+/// The UUID of a gamepad reported by gilrs
+pub type GamepadUuid = [u8; 16];
+
+/// Settings of the gamepads specified in `[gamepads]`
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Gamepads {
+    /// For the gamepads not listed
+    pub default: GamepadSettings,
+    pub gamepads: HashMap<GamepadUuid, GamepadSettings>,
+}
+
+impl Gamepads {
+    pub fn get(&self, uuid: GamepadUuid) -> GamepadSettings {
+        self.gamepads.get(&uuid).copied().unwrap_or(self.default)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct GamepadSettings {
+    /// Calibrations of the sticks, indexed by `Stick`
+    pub sticks: [Calibration; 2],
+    /// Native codes of the back paddles, which gilrs can't name. `None` when unknown
+    pub paddles: [Option<u32>; 4],
+}
+
+/// How the raw positions of a stick are turned into the ones that make motion inputs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Calibration {
+    pub deadzone: DeadzoneShape,
+    /// Positions closer to the center than this are the center
+    pub inner_deadzone: f32,
+    /// Positions further than this are pushed all the way, for sticks that can't reach the edge
+    pub outer_deadzone: f32,
+    /// Whether the X and Y axes are flipped
+    pub invert: [bool; 2],
+    /// Factors of the X and Y axes, for sticks with uneven ranges
+    pub scale: [f32; 2],
+    /// Where the stick rests, which is taken as the center
+    pub offset: (f32, f32),
+    /// Whether the offset is learned while the stick rests inside the inner deadzone, or near the center without it
+    pub drift_compensation: bool,
+}
+
+impl Default for Calibration {
+    fn default() -> Self {
+        Calibration {
+            deadzone: DeadzoneShape::Square,
+            inner_deadzone: INNER_DEADZONE,
+            outer_deadzone: OUTER_DEADZONE,
+            invert: [false; 2],
+            scale: [1.0; 2],
+            offset: (0.0, 0.0),
+            drift_compensation: false,
+        }
+    }
+}
+
+/// How the distance from the center is measured against the deadzones
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum DeadzoneShape {
+    /// The further of the axes, which makes diagonals harder to reach
+    #[default]
+    Square,
+    /// The distance on the plane
+    Radial,
+    /// The distance on the plane, rescaled to start over from the inner deadzone
+    ScaledRadial,
+}
+
+names!(DeadzoneShape {
+    Square => "square",
+    Radial => "radial",
+    ScaledRadial => "scaled-radial",
+});
+
+// calibration of a stick specified in `[gamepads]`, which overrides the defaults one by one
+#[derive(Default, Clone, Copy)]
+struct CalibrationOverrides {
+    deadzone: Option<DeadzoneShape>,
+    inner_deadzone: Option<f32>,
+    outer_deadzone: Option<f32>,
+    invert: [Option<bool>; 2],
+    scale: [Option<f32>; 2],
+    offset: (Option<f32>, Option<f32>),
+    drift_compensation: Option<bool>,
+}
+
+impl CalibrationOverrides {
+    fn apply(&self, calibration: Calibration) -> Calibration {
+        Calibration {
+            deadzone: self.deadzone.unwrap_or(calibration.deadzone),
+            inner_deadzone: self.inner_deadzone.unwrap_or(calibration.inner_deadzone),
+            outer_deadzone: self.outer_deadzone.unwrap_or(calibration.outer_deadzone),
+            invert: [0, 1].map(|i| self.invert[i].unwrap_or(calibration.invert[i])),
+            scale: [0, 1].map(|i| self.scale[i].unwrap_or(calibration.scale[i])),
+            offset: (
+                self.offset.0.unwrap_or(calibration.offset.0),
+                self.offset.1.unwrap_or(calibration.offset.1),
+            ),
+            drift_compensation: self.drift_compensation.unwrap_or(calibration.drift_compensation),
+        }
+    }
+}
+
+//----------------------------------------------------------------------------
+//
+//  The parser, which goes through the config line by line
+//
+//----------------------------------------------------------------------------
+
+#[derive(Default, Clone, Copy, PartialEq, Eq)]
+enum Section {
+    #[default]
+    Skills,
+    Keys,
+    Settings,
+    Fallbacks,
+    Arts,
+    Gamepads,
+    /// `[profile: NAME]`, which contains skill bindings just like `[skills]`
+    Profile,
+    // lines in unknown sections are ignored
+    Unknown,
+}
+
+impl Section {
+    // `None` for unclosed headers
+    fn of(header: &str) -> Option<Section> {
+        let name = header.strip_prefix('[')?.strip_suffix(']')?;
+        let section = match name.trim().to_ascii_uppercase().as_str() {
+            "SKILLS" => Section::Skills,
+            "KEYS" => Section::Keys,
+            "SETTINGS" => Section::Settings,
+            "FALLBACKS" => Section::Fallbacks,
+            "ARTS" => Section::Arts,
+            "GAMEPADS" => Section::Gamepads,
+            name if name.split_once(':').is_some_and(|(it, _)| it.trim_end() == "PROFILE") => Section::Profile,
+            _ => Section::Unknown,
+        };
+        Some(section)
+    }
+
+    fn binds_skills(self) -> bool {
+        matches!(self, Section::Skills | Section::Profile)
+    }
+}
+
+// bindings written in the base or in a profile. profiles are merged with the ones they inherit from at last
+#[derive(Default, Clone)]
+struct Layer {
+    name: String,
+    parent: Option<usize>,
+    line: usize,
+    arts: HashMap<Binding, Vec<UID>>,
+    // policies specified by the bindings and their lines
+    art_policies: HashMap<Binding, (ArtPolicy, usize)>,
+    tools: HashMap<Binding, Vec<UID>>,
+    tool_policies: HashMap<Binding, (ToolPolicy, usize)>,
+    modifiers: HashMap<Inputs, Vec<Modifier>>,
+}
+
+impl Layer {
+    fn used_inputs(&self) -> HashSet<Inputs> {
+        let bindings = self.arts.keys().chain(self.tools.keys());
+        bindings
+            .filter_map(|binding| match binding {
+                Binding::Motion(inputs) => Some(*inputs),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn build(&self, settings: &Settings) -> Profile {
+        let mut profile = Profile {
+            name: self.name.clone(),
+            ..Default::default()
+        };
+        for (binding, arts) in &self.arts {
+            let policy = self.art_policies.get(binding).map(|(it, _)| *it);
+            profile
+                .art_policies
+                .insert(*binding, policy.unwrap_or(settings.art_policy));
+            match binding {
+                Binding::Motion(inputs) => profile.arts.insert(*inputs, arts.clone()),
+                Binding::X1 => profile.arts_on_x1 = arts.clone(),
+                Binding::X2 => profile.arts_on_x2 = arts.clone(),
+                Binding::Button(button) => {
+                    profile.arts_on_buttons.insert(*button, arts.clone());
+                }
+                // rejected by the parser
+                Binding::Block => (),
+            }
+        }
+        for (binding, tools) in &self.tools {
+            let policy = self.tool_policies.get(binding).map(|(it, _)| *it);
+            profile
+                .tool_policies
+                .insert(*binding, policy.unwrap_or(settings.tool_policy));
+            match binding {
+                Binding::Motion(inputs) => profile.tools.insert(*inputs, tools.clone()),
+                Binding::Block => profile.tools_for_block = tools.clone(),
+                Binding::X1 => profile.tools_on_x1 = tools.clone(),
+                Binding::X2 => profile.tools_on_x2 = tools.clone(),
+                Binding::Button(button) => {
+                    profile.tools_on_buttons.insert(*button, tools.clone());
+                }
+            }
+        }
+
+        // fault tolernce
+        for inputs in self.used_inputs() {
+            let mut tolerances = settings.tolerances;
+            for modifier in self.modifiers.get(&inputs).into_iter().flatten() {
+                modifier.apply(&mut tolerances);
+            }
+            profile.tolerances.insert(inputs, tolerances);
+            let mut alternatives = possible_altenrnatives(inputs, tolerances);
+            if settings.diagonals {
+                // the stick passes through the diagonals on its way, e.g., ←↖↑ for ←↑
+                let sweeps = alternatives
+                    .iter()
+                    .chain([&inputs])
+                    .flat_map(|it| it.sweeps())
+                    .collect::<Vec<_>>();
+                alternatives.extend(sweeps);
+            }
+            for alt_inputs in alternatives {
+                if let Some(arts) = profile.arts.get(inputs).cloned()
+                    && profile.arts.get(alt_inputs).is_none()
+                {
+                    profile.arts.insert(alt_inputs, arts);
+                    let policy = profile.art_policies[&Binding::Motion(inputs)];
+                    profile.art_policies.insert(Binding::Motion(alt_inputs), policy);
+                }
+                if let Some(tools) = profile.tools.get(inputs).cloned()
+                    && profile.tools.get(alt_inputs).is_none()
+                {
+                    profile.tools.insert(alt_inputs, tools);
+                    let policy = profile.tool_policies[&Binding::Motion(inputs)];
+                    profile.tool_policies.insert(Binding::Motion(alt_inputs), policy);
+                }
+            }
+        }
+        profile
+    }
+}
+
+#[derive(Default)]
+struct Parser {
+    config: Config,
+    diagnostics: Vec<Diagnostic>,
+    line: usize,
+    section: Section,
+    // the base and then the profiles. skill bindings go to the last one entered
+    layers: Vec<Layer>,
+    layer: usize,
+    key_slots: HashMap<&'static str, usize>,
+    // fallbacks specified in the config and their lines
+    fallbacks: HashMap<UID, (UID, usize)>,
+    art_overrides: HashMap<UID, ArtOverrides>,
+    // `None` for every gamepad
+    calibration_overrides: HashMap<(Option<GamepadUuid>, Stick), CalibrationOverrides>,
+    paddle_overrides: HashMap<Option<GamepadUuid>, [Option<u32>; 4]>,
+    diagonals: Option<bool>,
+    // the first motion with diagonals and where it is, which is never made while diagonals are off
+    diagonal_binding: Option<(usize, usize, String)>,
+    // D-pad buttons bound to skills or keys and where they are, which also make motion inputs when the D-pad does
+    dpad_bindings: Vec<(usize, usize, Button)>,
+    notation: Option<Notation>,
+}
+
+impl Parser {
+    fn report(&mut self, column: usize, severity: Severity, message: String) {
+        self.diagnostics.push(Diagnostic {
+            line: self.line,
+            column,
+            severity,
+            message,
+        })
+    }
+
+    fn parse_line(&mut self, text: &str) {
+        let items = tokenize(text).collect::<Vec<_>>();
+        let Some(&(column, first)) = items.first() else {
+            return;
+        };
+        if first.starts_with('[') {
+            let header = items.iter().map(|(_, item)| *item).collect::<Vec<_>>().join(" ");
+            self.parse_section(column, &header);
+            return;
+        }
+        match self.section {
+            Section::Skills | Section::Profile => self.parse_skill(&items),
+            Section::Keys => self.parse_keys(&items),
+            Section::Settings => self.parse_settings(&items),
+            Section::Fallbacks => self.parse_fallback_table(&items),
+            Section::Arts => self.parse_art_settings(&items),
+            Section::Gamepads => self.parse_gamepad(&items),
+            Section::Unknown => (),
+        }
+    }
+
+    fn parse_section(&mut self, column: usize, header: &str) {
+        self.section = match Section::of(header) {
+            Some(Section::Unknown) => {
+                self.report(column, Severity::Error, format!("Unknown section `{header}`."));
+                Section::Unknown
+            }
+            Some(Section::Profile) => match self.parse_profile(header) {
+                Ok(layer) => {
+                    self.layer = layer;
+                    Section::Profile
+                }
+                Err(message) => {
+                    self.report(column, Severity::Error, message);
+                    Section::Unknown
+                }
+            },
+            Some(section) => {
+                self.layer = 0;
+                section
+            }
+            None => {
+                self.report(column, Severity::Error, format!("Unclosed section header `{header}`."));
+                Section::Unknown
+            }
+        }
+    }
+
+    // declares a profile and returns its index in the layers
+    fn parse_profile(&mut self, header: &str) -> Result<usize, String> {
+        let (name, parent) = parse_profile_header(header)?;
+        if name.eq_ignore_ascii_case(BASE_PROFILE) {
+            return Err(format!(
+                "`{BASE_PROFILE}` is reserved for the bindings outside of profiles."
+            ));
+        }
+        let find = |name: &str| self.layers.iter().position(|it| it.name.eq_ignore_ascii_case(name));
+        if let Some(index) = find(name) {
+            let line = self.layers[index].line;
+            return Err(format!("Profile `{name}` is already declared on line {line}."));
+        }
+        // profiles can only inherit from the ones declared before them, so there're never cycles
+        let parent = match parent {
+            Some(parent) if parent.eq_ignore_ascii_case(BASE_PROFILE) => 0,
+            Some(parent) => find(parent).ok_or_else(|| {
+                format!("Unknown profile `{parent}`. Profiles can only extend the ones declared before them.")
+            })?,
+            None => 0,
+        };
+        self.layers.push(Layer {
+            name: name.to_string(),
+            parent: Some(parent),
+            line: self.line,
+            ..Default::default()
+        });
+        Ok(self.layers.len() - 1)
+    }
+
+    fn parse_skill(&mut self, items: &[(usize, &str)]) {
+        // between IDs and inputs there're names of combat arts. They're ignored here
+        let (id_column, id) = items[0];
+        let id = match id.parse::<UID>() {
+            Ok(id) => id,
+            Err(_) => match parse_skill_name(id, &items[1..]) {
+                Ok(id) => id,
+                Err(message) => {
+                    self.report(id_column, Severity::Error, message);
+                    return;
+                }
+            },
+        };
+        // modifiers come after the binding, e.g., `5600  Floating Passage  ←→  !strict  +rev`
+        let mut items = &items[1..];
+        let mut modifiers = Vec::new();
+        let mut policy = None;
+        while let Some((&(column, item), rest)) = items.split_last()
+            && is_modifier(item)
+        {
+            // so does the policy, e.g., `7600  Shadowfall  ↑↑  @round-robin`
+            let result = match item.strip_prefix('@') {
+                Some(name) => parse_policy(name).map(|it| _ = policy.get_or_insert((column, item, it))),
+                None => parse_modifier(item).map(|it| modifiers.push((column, item, it))),
+            };
+            if let Err(message) = result {
+                self.report(column, Severity::Error, message);
+                return;
+            }
+            items = rest;
+        }
+        modifiers.reverse();
+        // fallbacks come right after the binding, e.g., `7100  Ichimonji: Double  ∅ > 5300`
+        let fallbacks = items.iter().any(|(_, item)| *item == ">");
+        if let Some(start) = items.iter().position(|(_, item)| *item == ">") {
+            let (rest, chain) = items.split_at(start);
+            if matches!(id, COMBART_ART_UID_MIN..=COMBART_ART_UID_MAX) {
+                self.parse_fallbacks((id_column, id), chain);
+            } else {
+                let message = "Fallbacks only apply to combat arts.".to_string();
+                self.report(chain[0].0, Severity::Error, message);
+            }
+            items = rest;
+        }
+        let Some(&(column, item)) = items.last() else {
+            let quiet = fallbacks || !modifiers.is_empty() || policy.is_some();
+            self.warn_unbound((id_column, id), items, quiet);
+            self.warn_useless_modifiers(&modifiers);
+            self.warn_useless_policy(policy);
+            return;
+        };
+        // filter out all illegal IDs to prevent possible bugs
+        let tool = match id {
+            PROSTHETIC_TOOL_UID_MIN..=PROSTHETIC_TOOL_UID_MAX => true,
+            COMBART_ART_UID_MIN..=COMBART_ART_UID_MAX => false,
+            _ => {
+                let message = format!(
+                    "UID {id} is out of range. Combat arts range from {COMBART_ART_UID_MIN} to {COMBART_ART_UID_MAX} \
+                    and prosthetic tools range from {PROSTHETIC_TOOL_UID_MIN} to {PROSTHETIC_TOOL_UID_MAX}."
+                );
+                self.report(id_column, Severity::Error, message);
+                return;
+            }
+        };
+        // in range but still possibly a typo
+        if Skill::find(id).is_none() {
+            let kind = if tool { "prosthetic tool" } else { "combat art" };
+            self.report(id_column, Severity::Warning, format!("UID {id} is not a known {kind}."));
+        }
+        let binding = match parse_binding(item) {
+            Ok(Some(Binding::Motion(inputs))) if !inputs.is_empty() => Binding::Motion(inputs),
+            Ok(Some(binding)) => {
+                self.warn_useless_modifiers(&modifiers);
+                binding
+            }
+            Ok(None) => {
+                let quiet = fallbacks || !modifiers.is_empty() || policy.is_some();
+                self.warn_unbound((id_column, id), items, quiet);
+                self.warn_useless_modifiers(&modifiers);
+                self.warn_useless_policy(policy);
+                return;
+            }
+            Err((offset, message)) => {
+                self.report(column + offset, Severity::Error, message);
+                return;
+            }
+        };
+
+        if let Binding::Button(button) = binding
+            && button.is_dpad()
+        {
+            self.dpad_bindings.push((self.line, column, button));
+        }
+        match (tool, binding) {
+            (true, _) if let Some((column, policy, Policy::Art(_))) = policy => {
+                let message = format!("`{policy}` only applies to combat arts.");
+                self.report(column, Severity::Error, message);
+            }
+            (false, _) if let Some((column, policy, Policy::Tool(_))) = policy => {
+                let message = format!("`{policy}` only applies to prosthetic tools.");
+                self.report(column, Severity::Error, message);
+            }
+            (true, binding) => {
+                if let Binding::Motion(inputs) = binding {
+                    self.use_inputs(inputs, (column, item), &modifiers);
+                }
+                // tools bound to `⛉` are used when BLOCK is heled, usually umbrella
+                let tools = self.layers[self.layer].tools.entry(binding).or_default();
+                if tools.contains(&id) {
+                    self.report(
+                        column,
+                        Severity::Warning,
+                        format!("Duplicate binding of {} to `{item}`.", describe(id)),
+                    );
+                } else {
+                    tools.push(id);
+                }
+                if let Some((column, _, Policy::Tool(policy))) = policy {
+                    let layer = &mut self.layers[self.layer];
+                    let prev = layer.tool_policies.insert(binding, (policy, self.line));
+                    let prev = prev.filter(|(it, _)| *it != policy).map(|(it, line)| (it.name(), line));
+                    self.warn_overridden_policy(column, item, prev);
+                }
+            }
+            (false, Binding::Block) => {
+                let message = format!("`{item}` can only be used on prosthetic tools.");
+                self.report(column, Severity::Error, message);
+            }
+            (false, binding) => {
+                if let Binding::Motion(inputs) = binding {
+                    self.use_inputs(inputs, (column, item), &modifiers);
+                }
+                // combat arts bound to the same input take turns or replace each other by the policy
+                let layer = &mut self.layers[self.layer];
+                let arts = layer.arts.entry(binding).or_default();
+                if arts.contains(&id) {
+                    self.report(
+                        column,
+                        Severity::Warning,
+                        format!("Duplicate binding of {} to `{item}`.", describe(id)),
+                    );
+                } else {
+                    arts.push(id);
+                }
+                if let Some((column, _, Policy::Art(policy))) = policy {
+                    let layer = &mut self.layers[self.layer];
+                    let prev = layer.art_policies.insert(binding, (policy, self.line));
+                    let prev = prev.filter(|(it, _)| *it != policy).map(|(it, line)| (it.name(), line));
+                    self.warn_overridden_policy(column, item, prev);
+                }
+            }
+        }
+    }
+
+    // a line in [fallbacks] looks like `7400  >  5800`, which is the same as `7400 ... > 5800` after a binding
+    fn parse_fallback_table(&mut self, items: &[(usize, &str)]) {
+        let (column, item) = items[0];
+        let Some(art) = self.parse_art(column, item, &items[1..], "Fallbacks only apply to combat arts.") else {
+            return;
+        };
+        if items.len() == 1 {
+            let message = format!("No fallback is specified for {}.", describe(art));
+            self.report(column, Severity::Error, message);
+            return;
+        }
+        self.parse_fallbacks((column, art), &items[1..]);
+    }
+
+    // the chain looks like `> 7400 > 5800`
+    fn parse_fallbacks(&mut self, (column, art): (usize, UID), chain: &[(usize, &str)]) {
+        let mut arts = vec![(column, art)];
+        for (i, pair) in chain.chunks(2).enumerate() {
+            let (column, arrow) = pair[0];
+            if arrow != ">" {
+                self.report(
+                    column,
+                    Severity::Error,
+                    format!("Unexpected token `{arrow}`. Expected `>`."),
+                );
+                return;
+            }
+            let Some(&(column, item)) = pair.get(1) else {
+                self.report(column, Severity::Error, "Expected a combat art after `>`.".to_string());
+                return;
+            };
+            let usage = "Fallbacks only apply to combat arts.";
+            let Some(art) = self.parse_art(column, item, &chain[i * 2 + 2..], usage) else {
+                return;
+            };
+            arts.push((column, art));
+        }
+        for pair in arts.windows(2) {
+            self.add_fallback(pair[0].1, pair[1]);
+        }
+    }
+
+    // a line in [arts] looks like `"One Mind"  sheathed=on  swapout_cooldown=240`
+    fn parse_art_settings(&mut self, items: &[(usize, &str)]) {
+        let (column, item) = items[0];
+        let usage = "Only combat arts can be specified in [arts].";
+        let Some(art) = self.parse_art(column, item, &items[1..], usage) else {
+            return;
+        };
+        if items.len() == 1 {
+            let message = format!("No setting is specified for {}.", describe(art));
+            self.report(column, Severity::Error, message);
+            return;
+        }
+        let mut overrides = self.art_overrides.get(&art).copied().unwrap_or_default();
+        for &(column, item) in &items[1..] {
+            let Some((name, value)) = item.split_once('=') else {
+                let message = format!("Unexpected token `{item}`. Expected `NAME=VALUE`, e.g., `sheathed=on`.");
+                self.report(column, Severity::Error, message);
+                continue;
+            };
+            let name = name.to_ascii_lowercase().replace('-', "_");
+            let result = match name.as_str() {
+                "sheathed" => parse_switch(value).map(|it| overrides.sheathed = Some(it)),
+                "swapout_cooldown" => {
+                    parse_setting(value, 0..=600).map(|it| overrides.swapout_cooldown = Some(Frames::standard(it)))
+                }
+                "block_injection_duration" => {
+                    parse_setting(value, 1..=60).map(|it| overrides.block_injection_duration = Some(it))
+                }
+                "attack_suppression" => parse_switch(value).map(|it| overrides.attack_suppression = Some(it)),
+                _ => Err(format!(
+                    "Unknown setting `{name}`. Expected one of sheathed, swapout_cooldown, \
+                    block_injection_duration and attack_suppression."
+                )),
+            };
+            if let Err(message) = result {
+                self.report(column, Severity::Error, message);
+            }
+        }
+        self.art_overrides.insert(art, overrides);
+    }
+
+    // a line in [gamepads] looks like `*  left  deadzone=radial  inner_deadzone=0.1`, where `*` means every gamepad
+    // or `*  paddles  paddle1=12  paddle2=13`
+    fn parse_gamepad(&mut self, items: &[(usize, &str)]) {
+        let (column, item) = items[0];
+        let uuid = match item {
+            "*" => None,
+            _ => match parse_uuid(item) {
+                Some(uuid) => Some(uuid),
+                None => {
+                    let message = format!("Illegal gamepad `{item}`. Expected `*` or a UUID of 32 hex digits.");
+                    self.report(column, Severity::Error, message);
+                    return;
+                }
+            },
+        };
+        let Some(&(column, item)) = items.get(1) else {
+            self.report(column, Severity::Error, "No stick is specified.".to_string());
+            return;
+        };
+        let stick = match item.to_ascii_lowercase().as_str() {
+            "left" => Stick::Left,
+            "right" => Stick::Right,
+            "paddles" => {
+                self.parse_paddles(uuid, column, &items[2..]);
+                return;
+            }
+            _ => {
+                let message = format!("Illegal stick `{item}`. Expected `left`, `right` or `paddles`.");
+                self.report(column, Severity::Error, message);
+                return;
+            }
+        };
+        if items.len() == 2 {
+            let message = format!("No setting is specified for the {item} stick.");
+            self.report(column, Severity::Error, message);
+            return;
+        }
+        let mut overrides = self
+            .calibration_overrides
+            .get(&(uuid, stick))
+            .copied()
+            .unwrap_or_default();
+        for &(column, item) in &items[2..] {
+            let Some((name, value)) = item.split_once('=') else {
+                let message = format!("Unexpected token `{item}`. Expected `NAME=VALUE`, e.g., `deadzone=radial`.");
+                self.report(column, Severity::Error, message);
+                continue;
+            };
+            let name = name.to_ascii_lowercase().replace('-', "_");
+            let result = match name.as_str() {
+                "deadzone" => DeadzoneShape::from_name(value)
+                    .map(|it| overrides.deadzone = Some(it))
+                    .ok_or_else(|| format!("Illegal value `{value}`. Expected {}.", DeadzoneShape::expected())),
+                // the ranges never overlap so that there's always room between the deadzones
+                "inner_deadzone" => parse_setting(value, 0.0..=0.5).map(|it| overrides.inner_deadzone = Some(it)),
+                "outer_deadzone" => parse_setting(value, 0.6..=1.0).map(|it| overrides.outer_deadzone = Some(it)),
+                "invert_x" => parse_switch(value).map(|it| overrides.invert[0] = Some(it)),
+                "invert_y" => parse_switch(value).map(|it| overrides.invert[1] = Some(it)),
+                "scale_x" => parse_setting(value, 0.1..=10.0).map(|it| overrides.scale[0] = Some(it)),
+                "scale_y" => parse_setting(value, 0.1..=10.0).map(|it| overrides.scale[1] = Some(it)),
+                "offset_x" => parse_setting(value, -0.5..=0.5).map(|it| overrides.offset.0 = Some(it)),
+                "offset_y" => parse_setting(value, -0.5..=0.5).map(|it| overrides.offset.1 = Some(it)),
+                "drift_compensation" => parse_switch(value).map(|it| overrides.drift_compensation = Some(it)),
+                _ => Err(format!(
+                    "Unknown setting `{name}`. Expected one of deadzone, inner_deadzone, outer_deadzone, \
+                    invert_x, invert_y, scale_x, scale_y, offset_x, offset_y and drift_compensation."
+                )),
+            };
+            if let Err(message) = result {
+                self.report(column, Severity::Error, message);
+            }
+        }
+        self.calibration_overrides.insert((uuid, stick), overrides);
+    }
+
+    // the native codes of the paddles differ from gamepad to gamepad, so they're never guessed
+    fn parse_paddles(&mut self, uuid: Option<GamepadUuid>, column: usize, items: &[(usize, &str)]) {
+        if items.is_empty() {
+            let message = "No code is specified for the paddles.".to_string();
+            self.report(column, Severity::Error, message);
+            return;
+        }
+        let mut paddles = self.paddle_overrides.get(&uuid).copied().unwrap_or_default();
+        for &(column, item) in items {
+            let Some((name, value)) = item.split_once('=') else {
+                let message = format!("Unexpected token `{item}`. Expected `NAME=VALUE`, e.g., `paddle1=12`.");
+                self.report(column, Severity::Error, message);
+                continue;
+            };
+            let name = name.to_ascii_lowercase();
+            let result = match name.strip_prefix("paddle").and_then(|it| it.parse::<usize>().ok()) {
+                Some(n @ 1..=4) => parse_setting(value, 0..=u32::MAX).map(|it| paddles[n - 1] = Some(it)),
+                _ => Err(format!(
+                    "Unknown setting `{name}`. Expected one of paddle1, paddle2, paddle3 and paddle4."
+                )),
+            };
+            if let Err(message) = result {
+                self.report(column, Severity::Error, message);
+            }
+        }
+        self.paddle_overrides.insert(uuid, paddles);
+    }
+
+    fn parse_art(&mut self, column: usize, item: &str, rest: &[(usize, &str)], usage: &str) -> Option<UID> {
+        let art = match item.parse::<UID>() {
+            Ok(art) => art,
+            Err(_) => match parse_skill_name(item, rest) {
+                Ok(art) => art,
+                Err(message) => {
+                    self.report(column, Severity::Error, message);
+                    return None;
+                }
+            },
+        };
+        if !matches!(art, COMBART_ART_UID_MIN..=COMBART_ART_UID_MAX) {
+            let message = format!("{} is not a combat art. {usage}", describe(art));
+            self.report(column, Severity::Error, message);
+            return None;
+        }
+        Some(art)
+    }
+
+    fn add_fallback(&mut self, art: UID, (column, fallback): (usize, UID)) {
+        match self.fallbacks.get(&art) {
+            Some(&(prev, _)) if prev == fallback => return,
+            Some(&(prev, line)) => {
+                let message = format!(
+                    "{} already falls back to {} on line {line}. {} is ignored.",
+                    describe(art),
+                    describe(prev),
+                    describe(fallback)
+                );
+                self.report(column, Severity::Error, message);
+                return;
+            }
+            None => (),
+        }
+        // the fallbacks so far never form cycles, so the chain always ends
+        let chain = self.config.fallback_chain(fallback).collect::<Vec<_>>();
+        if chain.contains(&art) {
+            let chain = std::iter::once(art)
+                .chain(chain.into_iter().take_while(|it| *it != art))
+                .chain([art])
+                .map(|it| describe(it).to_string())
+                .collect::<Vec<_>>()
+                .join(" > ");
+            self.report(
+                column,
+                Severity::Error,
+                format!("Fallbacks can not form a cycle: {chain}."),
+            );
+            return;
+        }
+        self.config.fallbacks.insert(art, fallback);
+        self.fallbacks.insert(art, (fallback, self.line));
+    }
+
+    fn use_inputs(&mut self, inputs: Inputs, (column, item): (usize, &str), modifiers: &[(usize, &str, Modifier)]) {
+        // sequences are printed in the notation of the first motion in the config
+        if !inputs.is_empty() {
+            self.notation.get_or_insert(Notation::of(item));
+        }
+        if inputs.iter().any(Input::is_diagonal) && self.diagonal_binding.is_none() {
+            self.diagonal_binding = Some((self.line, column, item.to_string()));
+        }
+        if !modifiers.is_empty() {
+            let entry = self.layers[self.layer].modifiers.entry(inputs).or_default();
+            entry.extend(modifiers.iter().map(|(_, _, modifier)| *modifier));
+        }
+    }
+
+    // the last word of a line without binding may be a misspelled one, e.g., `5300  Ichimonji  UUX`.
+    // it's only told apart from the name when the rest of the line names the skill, or when it's the only word
+    // and too short or too far from any name to be one. `quiet` when the line does something else or is already
+    // warned about
+    fn warn_unbound(&mut self, (id_column, id): (usize, UID), items: &[(usize, &str)], quiet: bool) {
+        let names_skill = |items: &[(usize, &str)]| {
+            let name = items.iter().map(|(_, it)| *it).collect::<Vec<_>>().join(" ");
+            Skill::find_by_name(&name).is_some_and(|it| it.uid == id)
+        };
+        if let Some((&(column, item), name)) = items.split_last()
+            && !names_skill(items)
+            && (names_skill(name) || name.is_empty() && Skill::find(id).is_some() && !looks_like_name(item))
+            && let Err(ParseInputsError::UnknownToken { offset, token }) = item.parse::<Inputs>()
+        {
+            let (offset, message) = match item.parse::<Button>() {
+                Ok(button) => (0, format!("`{button}` is taken by the game and can't be bound.")),
+                Err(_) => (offset, format!("Unknown token `{token}` in `{item}`.")),
+            };
+            self.report(column + offset, Severity::Error, message);
+        } else if !quiet {
+            let message = format!("{} is not bound to any input.", describe(id));
+            self.report(id_column, Severity::Warning, message);
+        }
+    }
+
+    fn warn_useless_modifiers(&mut self, modifiers: &[(usize, &str, Modifier)]) {
+        for &(column, modifier, _) in modifiers {
+            let message = format!("`{modifier}` has no effect since fault tolerance only applies to motion inputs.");
+            self.report(column, Severity::Warning, message);
+        }
+    }
+
+    fn warn_useless_policy(&mut self, policy: Option<(usize, &str, Policy)>) {
+        if let Some((column, policy, _)) = policy {
+            let message = format!("`{policy}` has no effect since the skill is not bound to any input.");
+            self.report(column, Severity::Warning, message);
+        }
+    }
+
+    // the policy written later takes effect
+    fn warn_overridden_policy(&mut self, column: usize, item: &str, prev: Option<(&str, usize)>) {
+        if let Some((prev, line)) = prev {
+            let message =
+                format!("The policy of `{item}` is already specified as `@{prev}` on line {line} and is overridden.");
+            self.report(column, Severity::Warning, message);
+        }
+    }
+
+    // a line in [keys] looks like `Up  W  UP`, which binds both W and ↑ to Up
+    fn parse_keys(&mut self, items: &[(usize, &str)]) {
+        let (slot_column, slot) = items[0];
+        let Some(slot) = parse_key_slot(slot) else {
+            let message =
+                format!("Unknown token `{slot}`. Expected one of Up, Right, Down, Left, M4, M5, Profile and Reset.");
+            self.report(slot_column, Severity::Error, message);
+            return;
+        };
+        let keys = &mut self.config.keys;
+        let result = match slot {
+            "Up" => parse_keys(&items[1..]).map(|it| keys.up = it),
+            "Right" => parse_keys(&items[1..]).map(|it| keys.right = it),
+            "Down" => parse_keys(&items[1..]).map(|it| keys.down = it),
+            "Left" => parse_keys(&items[1..]).map(|it| keys.left = it),
+            "M4" => parse_keys(&items[1..]).map(|it| keys.x1 = it),
+            "M5" => parse_keys(&items[1..]).map(|it| keys.x2 = it),
+            // gamepads can use the extra buttons, switch profiles and reset the combat art too
+            "Profile" => parse_keys(&items[1..]).map(|it| keys.profile = it),
+            _ => parse_keys(&items[1..]).map(|it| keys.reset = it),
+        };
+        if let Err(mut errors) = result {
+            if errors.is_empty() {
+                errors.push((slot_column, format!("No key is specified for {slot}.")));
+            }
+            for (column, message) in errors {
+                self.report(column, Severity::Error, message);
+            }
+            return;
+        }
+        for &(column, item) in &items[1..] {
+            if let Ok(Trigger::Button(button)) = item.parse()
+                && button.is_dpad()
+            {
+                self.dpad_bindings.push((self.line, column, button));
+            }
+        }
+        if let Some(line) = self.key_slots.insert(slot, self.line) {
+            let message = format!("Keys for {slot} are already specified on line {line} and are overridden.");
+            self.report(slot_column, Severity::Warning, message);
+        }
+    }
+
+    // a line in [settings] looks like `max_interval  10`
+    fn parse_settings(&mut self, items: &[(usize, &str)]) {
+        let (name_column, name) = items[0];
+        let Some(&(column, value)) = items.get(1) else {
+            self.report(
+                name_column,
+                Severity::Error,
+                format!("No value is specified for `{name}`."),
+            );
+            return;
+        };
+        if let Some(&(column, item)) = items.get(2) {
+            self.report(column, Severity::Error, format!("Unexpected token `{item}`."));
+            return;
+        }
+        let settings = &mut self.config.settings;
+        let name = name.to_ascii_lowercase().replace('-', "_");
+        // each rule of fault tolerance can be switched on and off, e.g., `rev_tolerance  off`
+        let tolerance = name.strip_suffix("_tolerance").and_then(Tolerance::from_name);
+        let result = match name.as_str() {
+            _ if let Some(tolerance) = tolerance => {
+                parse_switch(value).map(|it| settings.tolerances.set(tolerance, it))
+            }
+            "max_interval" => parse_setting(value, 1..=60).map(|it| settings.max_interval = Frames::standard(it)),
+            "max_delay" => parse_setting(value, 1..=60).map(|it| settings.max_delay = Frames::standard(it)),
+            "max_delay_for_single_input" => {
+                parse_setting(value, 0..=60).map(|it| settings.max_delay_for_single_input = Frames::standard(it))
+            }
+            "common_threshold" => parse_setting(value, 0.1..=1.0).map(|it| settings.common_threshold = it),
+            "rotate_threshold" => parse_setting(value, 0.1..=1.0).map(|it| settings.rotate_threshold = it),
+            "bounce_threshold" => parse_setting(value, 0.1..=1.0).map(|it| settings.bounce_threshold = it),
+            "gamepad_motion_source" => MotionSources::from_names(value)
+                .map(|it| settings.gamepad_motion_source = it)
+                .ok_or_else(|| {
+                    let names = list(&MotionSource::ALL.map(MotionSource::name));
+                    format!("Illegal value `{value}`. Expected `none` or any of {names} joined with `+`.")
+                }),
+            "right_common_threshold" => parse_setting(value, 0.1..=1.0).map(|it| settings.right_common_threshold = it),
+            "right_rotate_threshold" => parse_setting(value, 0.1..=1.0).map(|it| settings.right_rotate_threshold = it),
+            "right_bounce_threshold" => parse_setting(value, 0.1..=1.0).map(|it| settings.right_bounce_threshold = it),
+            "device_arbitration" => Arbitration::from_name(value)
+                .map(|it| settings.device_arbitration = it)
+                .ok_or_else(|| format!("Illegal value `{value}`. Expected {}.", Arbitration::expected())),
+            "keyboard_activity_timeout" => {
+                parse_setting(value, 1..=600).map(|it| settings.keyboard_activity_timeout = Frames::standard(it))
+            }
+            "gamepad_activity_timeout" => {
+                parse_setting(value, 1..=600).map(|it| settings.gamepad_activity_timeout = Frames::standard(it))
+            }
+            "diagonals" => parse_switch(value).map(|it| self.diagonals = Some(it)),
+            "diagonal_sector" => parse_setting(value, 10.0..=80.0).map(|it| settings.diagonal_sector = it),
+            "chord_window" => parse_setting(value, 0..=10).map(|it| settings.chord_window = Frames::standard(it)),
+            "socd" => Socd::from_name(value)
+                .map(|it| settings.socd = it)
+                .ok_or_else(|| format!("Illegal value `{value}`. Expected {}.", Socd::expected())),
+            "block_injection_duration" => parse_setting(value, 1..=60).map(|it| settings.block_injection_duration = it),
+            "attack_suppression_duration" => {
+                parse_setting(value, 0..=10).map(|it| settings.attack_suppression_duration = it)
+            }
+            "prosthetic_suppression_duration" => {
+                parse_setting(value, 0..=10).map(|it| settings.prosthetic_suppression_duration = it)
+            }
+            "prosthetic_rollback_countdown" => {
+                parse_setting(value, 0..=600).map(|it| settings.prosthetic_rollback_countdown = Frames::standard(it))
+            }
+            "previous_slot_rollback_countdown" => {
+                parse_setting(value, 0..=600).map(|it| settings.previous_slot_rollback_countdown = Frames::standard(it))
+            }
+            "sticky_rollback_countdown" => {
+                parse_setting(value, 0..=600).map(|it| settings.sticky_rollback_countdown = Frames::standard(it))
+            }
+            "sticky_art" => parse_switch(value).map(|it| settings.sticky_art = it),
+            "rollback" => Rollback::from_name(value)
+                .map(|it| settings.rollback = it)
+                .ok_or_else(|| format!("Illegal value `{value}`. Expected {}.", Rollback::expected())),
+            "art_policy" => ArtPolicy::from_name(value)
+                .map(|it| settings.art_policy = it)
+                .ok_or_else(|| format!("Illegal value `{value}`. Expected {}.", ArtPolicy::expected())),
+            "tool_policy" => ToolPolicy::from_name(value)
+                .map(|it| settings.tool_policy = it)
+                .ok_or_else(|| format!("Illegal value `{value}`. Expected {}.", ToolPolicy::expected())),
+            _ => {
+                self.report(name_column, Severity::Error, format!("Unknown setting `{name}`."));
+                return;
+            }
+        };
+        if let Err(message) = result {
+            self.report(column, Severity::Error, message);
+        }
+    }
+
+    fn finish(mut self) -> (Config, Vec<Diagnostic>) {
+        // profiles start with what they inherit and override it binding by binding
+        let mut layers = Vec::<Layer>::with_capacity(self.layers.len());
+        for layer in self.layers {
+            let mut merged = match layer.parent {
+                Some(parent) => layers[parent].clone(),
+                None => Layer::default(),
+            };
+            merged.arts.extend(layer.arts);
+            merged.art_policies.extend(layer.art_policies);
+            merged.tools.extend(layer.tools);
+            merged.tool_policies.extend(layer.tool_policies);
+            // modifiers of the profile are applied after the inherited ones
+            for (inputs, modifiers) in layer.modifiers {
+                merged.modifiers.entry(inputs).or_default().extend(modifiers);
+            }
+            merged.name = layer.name;
+            layers.push(merged);
+        }
+
+        let config = &mut self.config;
+        // diagonals are opt-in since they change how every motion is made on the stick
+        config.settings.diagonals = self.diagonals.unwrap_or_default();
+        if !config.settings.diagonals
+            && let Some((line, column, item)) = self.diagonal_binding.take()
+        {
+            self.diagnostics.push(Diagnostic {
+                line,
+                column,
+                severity: Severity::Warning,
+                message: format!("`{item}` is never made unless `diagonals` is switched `on` in [settings]."),
+            });
+        }
+        config.notation = self.notation.unwrap_or_default();
+        if config.settings.gamepad_motion_source.contains(MotionSource::DPad) {
+            for (line, column, button) in self.dpad_bindings.drain(..) {
+                let message = format!("`{button}` also makes motion inputs since `gamepad_motion_source` has `dpad`.");
+                self.diagnostics.push(Diagnostic {
+                    line,
+                    column,
+                    severity: Severity::Warning,
+                    message,
+                });
+            }
+        }
+
+        // workarounds of the combat arts
+        for &(art, sheathed, swapout_cooldown) in ART_SETTINGS {
+            let settings = ArtSettings {
+                sheathed,
+                swapout_cooldown: Frames::standard(swapout_cooldown),
+                ..ArtSettings::new(&config.settings)
+            };
+            config.art_settings.insert(art, settings);
+        }
+        for (art, overrides) in self.art_overrides {
+            let settings = config.art(art);
+            let settings = ArtSettings {
+                sheathed: overrides.sheathed.unwrap_or(settings.sheathed),
+                swapout_cooldown: overrides.swapout_cooldown.unwrap_or(settings.swapout_cooldown),
+                block_injection_duration: overrides
+                    .block_injection_duration
+                    .unwrap_or(settings.block_injection_duration),
+                attack_suppression: overrides.attack_suppression.unwrap_or(settings.attack_suppression),
+            };
+            config.art_settings.insert(art, settings);
+        }
+
+        // settings of the gamepads. gamepads start with what's specified for every gamepad
+        let overrides = &self.calibration_overrides;
+        let calibrate = |uuid, stick| {
+            let calibration = Calibration::default();
+            let calibration = overrides
+                .get(&(None, stick))
+                .map_or(calibration, |it| it.apply(calibration));
+            match uuid {
+                Some(uuid) => overrides
+                    .get(&(Some(uuid), stick))
+                    .map_or(calibration, |it| it.apply(calibration)),
+                None => calibration,
+            }
+        };
+        let paddle_overrides = &self.paddle_overrides;
+        let paddles = |uuid: Option<GamepadUuid>| {
+            let paddles = paddle_overrides.get(&None).copied().unwrap_or_default();
+            let overrides = paddle_overrides.get(&uuid).copied().unwrap_or_default();
+            [0, 1, 2, 3].map(|i| overrides[i].or(paddles[i]))
+        };
+        let settings = |uuid| GamepadSettings {
+            sticks: [Stick::Left, Stick::Right].map(|stick| calibrate(uuid, stick)),
+            paddles: paddles(uuid),
+        };
+        config.gamepads.default = settings(None);
+        let uuids = overrides
+            .keys()
+            .map(|(uuid, _)| *uuid)
+            .chain(paddle_overrides.keys().copied());
+        for uuid in uuids.flatten() {
+            config.gamepads.gamepads.insert(uuid, settings(Some(uuid)));
+        }
+
+        let mut profiles = layers.iter().map(|layer| layer.build(&config.settings));
+        config.base = profiles.next().unwrap_or_default();
+        config.base.name = BASE_PROFILE.to_string();
+        config.profiles = profiles.collect();
+
+        // the same key for different directions makes motion inputs impossible
+        let keys = &config.keys;
+        let directions = [
+            ("Up", &keys.up),
+            ("Right", &keys.right),
+            ("Down", &keys.down),
+            ("Left", &keys.left),
+        ];
+        for (i, (slot, keys)) in directions.iter().enumerate() {
+            for (other_slot, other_keys) in &directions[i + 1..] {
+                if let Some(key) = keys.iter().find(|key| other_keys.contains(key)) {
+                    // blame the latter line that specifies either of them
+                    let line = [slot, other_slot].iter().filter_map(|it| self.key_slots.get(*it)).max();
+                    self.diagnostics.push(Diagnostic {
+                        line: line.copied().unwrap_or_default(),
+                        column: 1,
+                        severity: Severity::Error,
+                        message: format!("{key} is used for both {slot} and {other_slot}."),
+                    });
+                }
+            }
+        }
+        (self.config, self.diagnostics)
+    }
+}
+
+//----------------------------------------------------------------------------
+//
+//  Fault tolerance, which binds the skills to more sequences than specified
+//
+//----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tolerance {
+    /// `→←` for `←→`, so that players can press A and D at the same time
+    Rev,
+    /// `↑↑↑` for `↑↑`
+    Smash,
+    /// `←↑→` and `←↓→` for `←→`, for gamepads
+    Semicircle,
+    /// Other permutations of `←↓→`
+    Shuffle,
+}
+
+names!(Tolerance {
+    Rev => "rev",
+    Smash => "smash",
+    Semicircle => "semicircle",
+    Shuffle => "shuffle",
+});
+
+/// A set of fault tolerance rules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Tolerances(u8);
+
+impl Tolerances {
+    pub const NONE: Tolerances = Tolerances(0);
+    pub const ALL: Tolerances = Tolerances(0b1111);
+
+    pub fn contains(self, tolerance: Tolerance) -> bool {
+        self.0 & 1 << tolerance as u8 != 0
+    }
+
+    pub fn set(&mut self, tolerance: Tolerance, on: bool) {
+        if on {
+            self.0 |= 1 << tolerance as u8;
+        } else {
+            self.0 &= !(1 << tolerance as u8);
+        }
+    }
+}
+
+impl Default for Tolerances {
+    fn default() -> Self {
+        Tolerances::ALL
+    }
+}
+
+impl<const N: usize> From<[Tolerance; N]> for Tolerances {
+    fn from(array: [Tolerance; N]) -> Self {
+        let mut tolerances = Tolerances::NONE;
+        for tolerance in array {
+            tolerances.set(tolerance, true);
+        }
+        tolerances
+    }
+}
+
+// modifiers of the rules on a single line, applied in the order they're written
+#[derive(Debug, Clone, Copy)]
+enum Modifier {
+    Strict,
+    Enable(Tolerance),
+    Disable(Tolerance),
+}
+
+impl Modifier {
+    fn apply(self, tolerances: &mut Tolerances) {
+        match self {
+            Modifier::Strict => *tolerances = Tolerances::NONE,
+            Modifier::Enable(tolerance) => tolerances.set(tolerance, true),
+            Modifier::Disable(tolerance) => tolerances.set(tolerance, false),
+        }
+    }
+}
+
+impl fmt::Display for Modifier {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Modifier::Strict => f.write_str("!strict"),
+            Modifier::Enable(tolerance) => write!(f, "+{}", tolerance.name()),
+            Modifier::Disable(tolerance) => write!(f, "-{}", tolerance.name()),
+        }
+    }
+}
+
+// a lone `-` can be part of the names. policies like `@round-robin` are written among the modifiers
+fn is_modifier(item: &str) -> bool {
+    item.starts_with('+') || item.starts_with('!') || item.starts_with('@') || item.starts_with('-') && item.len() > 1
+}
+
+fn parse_modifier(item: &str) -> Result<Modifier, String> {
+    let (sign, name) = item.split_at(1);
+    match (sign, Tolerance::from_name(name)) {
+        ("!", _) if name.eq_ignore_ascii_case("strict") => Ok(Modifier::Strict),
+        ("+", Some(tolerance)) => Ok(Modifier::Enable(tolerance)),
+        ("-", Some(tolerance)) => Ok(Modifier::Disable(tolerance)),
+        _ => Err(format!(
+            "Unknown modifier `{item}`. Expected `!strict` or `+`/`-` followed by {}.",
+            Tolerance::expected()
+        )),
+    }
+}
+
+//----------------------------------------------------------------------------
+//
+//  Problems found when parsing the config
+//
+//----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+    Warning,
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// 1-based line number
+    pub line: usize,
+    /// 1-based column number, counted in characters
+    pub column: usize,
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Severity::Warning => f.write_str("warning"),
+            Severity::Error => f.write_str("error"),
+        }
+    }
 }
 
-impl Config {
-    pub fn open(path: impl AsRef<Path>) -> io::Result<Config> {
-        Ok(fs::read_to_string(path)?.to_ascii_uppercase().into())
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} at {}:{}: {}",
+            self.severity, self.line, self.column, self.message
+        )
     }
 }
 
-impl Default for Config {
-    fn default() -> Self {
-        Config {
-            arts: InputsTrie::new(),
-            tools: InputsTrie::new(),
-            tools_for_block: &[],
-            tools_on_x1: &[],
-            tools_on_x2: &[],
+//----------------------------------------------------------------------------
+//
+//  A lossless syntax tree of the config, which can be written back out as it
+//  is or formatted canonically
+//
+//----------------------------------------------------------------------------
+
+/// An item on a line along with the whitespace before it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Token {
+    pub space: String,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Node {
+    /// A line with nothing but whitespace and comments
+    Blank,
+    /// `[keys]`, which may be written in several items like `[ keys ]`
+    Section(Vec<Token>),
+    /// `7600  Shadowfall  ↑↑ > 6000  +rev` in `[skills]`. The binding is absent when the line only names the skill
+    Skill {
+        /// The UID or the name of the skill
+        skill: Token,
+        name: Vec<Token>,
+        binding: Option<Token>,
+        /// `>` and the combat arts to fall back to, one after another
+        fallbacks: Vec<Token>,
+        modifiers: Vec<Token>,
+    },
+    /// `max_interval  10` in other sections
+    Entry { name: Token, values: Vec<Token> },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Line {
+    pub node: Node,
+    pub comment: Option<Token>,
+    /// Whitespace at the end of the line, including the `\r` of CRLF
+    pub trailing: String,
+}
+
+/// The lines of the config. Writing it out with `Display` gives back exactly the source it's parsed from
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Document {
+    pub lines: Vec<Line>,
+}
+
+impl Document {
+    pub fn parse(src: impl AsRef<str>) -> Document {
+        let mut section = Section::default();
+        let mut lines = Vec::new();
+        for text in src.as_ref().split('\n') {
+            let line = Line::parse(text, section);
+            if let Node::Section(header) = &line.node {
+                section = Section::of(&join(header, " ")).unwrap_or(Section::Unknown);
+            }
+            lines.push(line);
+        }
+        Document { lines }
+    }
+
+    /// Re-aligns the columns, normalizes the glyphs and the names, and removes indentation and trailing
+    /// whitespace. Comments are kept where they are
+    pub fn format(&mut self) {
+        let mut section = Section::default();
+        for line in self.lines.iter_mut() {
+            if let Node::Section(header) = &line.node {
+                section = Section::of(&join(header, " ")).unwrap_or(Section::Unknown);
+            }
+            line.normalize(section);
+        }
+        // lines between 2 section headers are aligned together
+        let mut start = 0;
+        for end in 1..=self.lines.len() {
+            if end == self.lines.len() || matches!(self.lines[end].node, Node::Section(_)) {
+                align(&mut self.lines[start..end]);
+                start = end;
+            }
         }
     }
 }
 
-impl<S: AsRef<str>> From<S> for Config {
-    fn from(value: S) -> Config {
-        let mut config = Config::default();
-        let mut tools = HashMap::<Inputs, Vec<UID>>::new();
-        let mut tools_for_block = Vec::new();
-        let mut tools_on_x1 = Vec::new();
-        let mut tools_on_x2 = Vec::new();
-        let mut used_inputs = HashSet::new();
-        for line in value.as_ref().lines() {
-            let mut items = line.split_whitespace().take_while(|item| !item.starts_with("#"));
-            // between IDs and inputs there're names of combat arts. They're ignored here
-            let Some(id) = items.next().and_then(|id| id.parse::<UID>().ok()) else {
-                continue;
-            };
-            let Some(inputs) = items.last() else {
-                continue;
-            };
-            // filter out all illegal IDs to prevent possible bugs
-            let tool = match id {
-                PROSTHETIC_TOOL_UID_MIN..=PROSTHETIC_TOOL_UID_MAX => true,
-                COMBART_ART_UID_MIN..=COMBART_ART_UID_MAX => false,
-                _ => {
-                    log::warn!("Illegal ID {id} is ignored.");
-                    continue;
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, line) in self.lines.iter().enumerate() {
+            if i != 0 {
+                f.write_str("\n")?;
+            }
+            write!(f, "{line}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Line {
+    fn parse(text: &str, section: Section) -> Line {
+        let mut tokens = Vec::new();
+        let mut comment = None;
+        let mut rest = text;
+        let trailing = loop {
+            let item = rest.trim_start();
+            let space = rest[..rest.len() - item.len()].to_string();
+            if item.is_empty() {
+                break space;
+            }
+            if item.starts_with('#') {
+                let text = item.trim_end();
+                comment = Some(Token {
+                    space,
+                    text: text.to_string(),
+                });
+                break item[text.len()..].to_string();
+            }
+            let len = item_len(item);
+            tokens.push(Token {
+                space,
+                text: item[..len].to_string(),
+            });
+            rest = &item[len..];
+        };
+
+        let node = match tokens.first() {
+            None => Node::Blank,
+            Some(first) if first.text.starts_with('[') => Node::Section(tokens),
+            Some(_) if section.binds_skills() => {
+                let mut name = tokens.split_off(1);
+                let count = name.iter().rev().take_while(|it| is_modifier(&it.text)).count();
+                let modifiers = name.split_off(name.len() - count);
+                let start = name.iter().position(|it| it.text == ">").unwrap_or(name.len());
+                let fallbacks = name.split_off(start);
+                // the last item is a binding unless it's a part of the name
+                let binding = match name.last() {
+                    Some(last) if !matches!(parse_binding(&last.text), Ok(None)) => name.pop(),
+                    _ => None,
+                };
+                Node::Skill {
+                    skill: tokens.remove(0),
+                    name,
+                    binding,
+                    fallbacks,
+                    modifiers,
                 }
-            };
+            }
+            Some(_) => {
+                let values = tokens.split_off(1);
+                Node::Entry {
+                    name: tokens.remove(0),
+                    values,
+                }
+            }
+        };
+        Line {
+            node,
+            comment,
+            trailing,
+        }
+    }
 
-            if tool {
-                // tools to use when BLOCK is heled, usually umbrella
-                match inputs {
-                    "X1" | "M4" => tools_on_x1.push(id),
-                    "X2" | "M5" => tools_on_x2.push(id),
-                    "⛉" | "BLOCK" => tools_for_block.push(id),
-                    other => {
-                        if let Some(inputs) = parse_motion(other) {
-                            used_inputs.insert(inputs);
-                            tools.entry(inputs).or_insert_with(Vec::new).push(id);
+    fn normalize(&mut self, section: Section) {
+        match &mut self.node {
+            Node::Blank => (),
+            Node::Section(header) => {
+                let text = match Section::of(&join(header, " ")) {
+                    Some(Section::Skills) => "[skills]".to_string(),
+                    Some(Section::Keys) => "[keys]".to_string(),
+                    Some(Section::Settings) => "[settings]".to_string(),
+                    Some(Section::Fallbacks) => "[fallbacks]".to_string(),
+                    Some(Section::Arts) => "[arts]".to_string(),
+                    Some(Section::Gamepads) => "[gamepads]".to_string(),
+                    Some(Section::Profile) => match parse_profile_header(&join(header, " ")) {
+                        Ok((name, None)) => format!("[profile: {name}]"),
+                        Ok((name, Some(parent))) => format!("[profile: {name} extends {parent}]"),
+                        Err(_) => join(header, " "),
+                    },
+                    _ => join(header, " "),
+                };
+                *header = vec![Token {
+                    space: String::new(),
+                    text,
+                }];
+            }
+            Node::Skill { binding, modifiers, .. } => {
+                if let Some(binding) = binding {
+                    match parse_binding(&binding.text) {
+                        Ok(Some(Binding::Motion(inputs))) => {
+                            binding.text = inputs.display(Notation::of(&binding.text)).to_string();
                         }
+                        Ok(Some(Binding::Block)) => binding.text = "⛉".to_string(),
+                        Ok(Some(Binding::X1)) => binding.text = "M4".to_string(),
+                        Ok(Some(Binding::X2)) => binding.text = "M5".to_string(),
+                        Ok(Some(Binding::Button(button))) => binding.text = button.to_string(),
+                        Ok(None) | Err(_) => (),
                     }
                 }
-            } else {
-                if let Some(inputs) = parse_motion(inputs) {
-                    used_inputs.insert(inputs);
-                    config.arts.insert(inputs, id);
+                for modifier in modifiers {
+                    if let Some(name) = modifier.text.strip_prefix('@')
+                        && let Ok(policy) = parse_policy(name)
+                    {
+                        modifier.text = match policy {
+                            Policy::Art(policy) => format!("@{}", policy.name()),
+                            Policy::Tool(policy) => format!("@{}", policy.name()),
+                        };
+                    } else if let Ok(parsed) = parse_modifier(&modifier.text) {
+                        modifier.text = parsed.to_string();
+                    }
+                }
+            }
+            Node::Entry { name, values } if section == Section::Keys => {
+                if let Some(slot) = parse_key_slot(&name.text) {
+                    name.text = slot.to_string();
+                }
+                for value in values {
+                    if let Ok(key) = value.text.parse::<Trigger>() {
+                        value.text = key.to_string();
+                    }
+                }
+            }
+            Node::Entry { name, values } if section == Section::Settings => {
+                name.text = name.text.to_ascii_lowercase().replace('-', "_");
+                for value in values {
+                    if let Ok(on) = parse_switch(&value.text) {
+                        value.text = if on { "on" } else { "off" }.to_string();
+                    }
                 }
             }
+            Node::Entry { values, .. } if matches!(section, Section::Arts | Section::Gamepads) => {
+                for value in values {
+                    if let Some((name, text)) = value.text.split_once('=') {
+                        let name = name.to_ascii_lowercase().replace('-', "_");
+                        let text = match parse_switch(text) {
+                            Ok(on) => if on { "on" } else { "off" }.to_string(),
+                            Err(_) => text.to_string(),
+                        };
+                        value.text = format!("{name}={text}");
+                    }
+                }
+            }
+            Node::Entry { .. } => (),
         }
+        // only keeps the `\r` of CRLF
+        self.trailing = if self.trailing.ends_with('\r') { "\r" } else { "" }.to_string();
+    }
+}
 
-        // leak vecs into slices
-        for (inputs, tools) in tools {
-            config.tools.insert(inputs, tools.leak());
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut tokens = Vec::new();
+        match &self.node {
+            Node::Blank => (),
+            Node::Section(header) => tokens.extend(header),
+            Node::Skill {
+                skill,
+                name,
+                binding,
+                fallbacks,
+                modifiers,
+            } => {
+                tokens.push(skill);
+                tokens.extend(name);
+                tokens.extend(binding);
+                tokens.extend(fallbacks);
+                tokens.extend(modifiers);
+            }
+            Node::Entry { name, values } => {
+                tokens.push(name);
+                tokens.extend(values);
+            }
+        }
+        tokens.extend(&self.comment);
+        for token in tokens {
+            write!(f, "{}{}", token.space, token.text)?;
         }
-        config.tools_for_block = tools_for_block.leak();
-        config.tools_on_x1 = tools_on_x1.leak();
-        config.tools_on_x2 = tools_on_x2.leak();
+        f.write_str(&self.trailing)
+    }
+}
 
-        // fault tolernce
-        for inputs in used_inputs {
-            for alt_inputs in possible_altenrnatives(inputs) {
-                if let Some(art) = config.arts.get(inputs) {
-                    config.arts.try_insert(alt_inputs, art);
+// columns are as wide as their widest items plus 2 spaces, except for skills, which only take 1 extra space
+// so that 5-digit UIDs of prosthetic tools and 4-digit ones of combat arts line up nicely
+fn align(lines: &mut [Line]) {
+    let widest = |column: fn(&Node) -> Option<usize>| lines.iter().filter_map(|it| column(&it.node)).max();
+    let skill_width = widest(|node| match node {
+        Node::Skill { skill, .. } => Some(width(&skill.text) + 1),
+        _ => None,
+    });
+    // names without anything after them don't take the column
+    let name_width = widest(|node| match node {
+        Node::Skill {
+            name,
+            binding,
+            fallbacks,
+            modifiers,
+            ..
+        } if binding.is_some() || !fallbacks.is_empty() || !modifiers.is_empty() => Some(width(&join(name, " ")) + 2),
+        _ => None,
+    });
+    // fallbacks share the column with the binding
+    let binding_width = widest(|node| match node {
+        Node::Skill {
+            binding,
+            fallbacks,
+            modifiers,
+            ..
+        } if !modifiers.is_empty() && (binding.is_some() || !fallbacks.is_empty()) => {
+            let tokens = binding.iter().chain(fallbacks).cloned().collect::<Vec<_>>();
+            Some(width(&join(&tokens, " ")) + 2)
+        }
+        _ => None,
+    });
+    let entry_width = widest(|node| match node {
+        Node::Entry { name, .. } => Some(width(&name.text) + 2),
+        _ => None,
+    });
+    let (skill_width, name_width, binding_width, entry_width) = (
+        skill_width.unwrap_or_default(),
+        name_width.unwrap_or_default(),
+        binding_width.unwrap_or_default(),
+        entry_width.unwrap_or_default(),
+    );
+
+    let mut content_widths = Vec::new();
+    for line in lines.iter_mut() {
+        let mut cursor = Cursor::default();
+        match &mut line.node {
+            Node::Blank => (),
+            Node::Section(header) => {
+                for token in header {
+                    cursor.follow(token, 1);
+                }
+            }
+            Node::Skill {
+                skill,
+                name,
+                binding,
+                fallbacks,
+                modifiers,
+            } => {
+                cursor.place(skill, 0);
+                for (i, token) in name.iter_mut().enumerate() {
+                    match i {
+                        0 => cursor.place(token, skill_width),
+                        _ => cursor.follow(token, 1),
+                    }
+                }
+                for (i, token) in binding.iter_mut().chain(fallbacks).enumerate() {
+                    match i {
+                        0 => cursor.place(token, skill_width + name_width),
+                        _ => cursor.follow(token, 1),
+                    }
+                }
+                for (i, modifier) in modifiers.iter_mut().enumerate() {
+                    match i {
+                        0 => cursor.place(modifier, skill_width + name_width + binding_width),
+                        _ => cursor.follow(modifier, 2),
+                    }
                 }
-                if let Some(tools) = config.tools.get(inputs) {
-                    config.tools.try_insert(alt_inputs, tools);
+            }
+            Node::Entry { name, values } => {
+                cursor.place(name, 0);
+                for (i, value) in values.iter_mut().enumerate() {
+                    match i {
+                        0 => cursor.place(value, entry_width),
+                        _ => cursor.follow(value, 2),
+                    }
                 }
             }
         }
-        config
+        content_widths.push(cursor.column);
+    }
+
+    // trailing comments line up as well, while the comments on their own lines stay at the start
+    let comment_column = lines
+        .iter()
+        .zip(content_widths.iter())
+        .filter(|(line, width)| line.comment.is_some() && **width != 0)
+        .map(|(_, width)| width + 2)
+        .max()
+        .unwrap_or_default();
+    for (line, column) in lines.iter_mut().zip(content_widths) {
+        if let Some(comment) = &mut line.comment {
+            let mut cursor = Cursor { column };
+            cursor.place(comment, if column == 0 { 0 } else { comment_column });
+        }
+    }
+}
+
+// places tokens one after another, keeping track of the width of the line so far
+#[derive(Default)]
+struct Cursor {
+    column: usize,
+}
+
+impl Cursor {
+    // tokens are always separated by whitespace, even if the column is already passed
+    fn place(&mut self, token: &mut Token, column: usize) {
+        let mut gap = column.saturating_sub(self.column);
+        if self.column != 0 {
+            gap = gap.max(1);
+        }
+        token.space = " ".repeat(gap);
+        self.column += gap + width(&token.text);
+    }
+
+    // the first token of the line never follows anything
+    fn follow(&mut self, token: &mut Token, gap: usize) {
+        let column = if self.column == 0 { 0 } else { self.column + gap };
+        self.place(token, column);
+    }
+}
+
+fn join(tokens: &[Token], separator: &str) -> String {
+    tokens
+        .iter()
+        .map(|it| it.text.as_str())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+// CJK characters take 2 columns in monospace fonts
+fn width(text: &str) -> usize {
+    text.chars()
+        .map(|ch| match ch as u32 {
+            0x1100..=0x115F
+            | 0x2E80..=0x303E
+            | 0x3041..=0x33FF
+            | 0x3400..=0x4DBF
+            | 0x4E00..=0x9FFF
+            | 0xA000..=0xA4CF
+            | 0xAC00..=0xD7A3
+            | 0xF900..=0xFAFF
+            | 0xFE30..=0xFE4F
+            | 0xFF00..=0xFF60
+            | 0xFFE0..=0xFFE6 => 2,
+            _ => 1,
+        })
+        .sum()
+}
+
+//----------------------------------------------------------------------------
+//
+//  Parsing of the columns
+//
+//----------------------------------------------------------------------------
+
+/// What a skill is bound to besides its name in the config
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Binding {
+    Motion(Inputs),
+    Block,
+    X1,
+    X2,
+    /// One of the extra buttons on gamepads, such as `R3` and back paddles
+    Button(Button),
+}
+
+// splits the line into items and their columns. everything after `#` is a comment
+fn tokenize(line: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut rest = line;
+    std::iter::from_fn(move || {
+        let item = rest.trim_start();
+        if item.is_empty() || item.starts_with('#') {
+            return None;
+        }
+        let len = item_len(item);
+        rest = &item[len..];
+        let offset = item.as_ptr() as usize - line.as_ptr() as usize;
+        Some((line[..offset].chars().count() + 1, &item[..len]))
+    })
+}
+
+// the length of the item at the start of `s`. quoted names like `"Spiral Spear"` make single items
+// even though they contain whitespace
+fn item_len(s: &str) -> usize {
+    let quoted = match s.strip_prefix('"').and_then(|it| it.find('"')) {
+        Some(end) => end + 2,
+        None => 0,
+    };
+    quoted + s[quoted..].find(char::is_whitespace).unwrap_or(s.len() - quoted)
+}
+
+// skills can be referred to by their names, e.g., `Shadowfall` or `"Spiral Spear"`
+fn parse_skill_name(item: &str, rest: &[(usize, &str)]) -> Result<UID, String> {
+    let name = match item.strip_prefix('"') {
+        Some(quoted) => quoted
+            .strip_suffix('"')
+            .ok_or_else(|| format!("Unclosed quote in `{item}`."))?,
+        None => item,
+    };
+    if let Some(skill) = Skill::find_by_name(name) {
+        return Ok(skill.uid);
+    }
+    // names with whitespace must be quoted
+    for len in 1..=rest.len() {
+        let name = std::iter::once(item)
+            .chain(rest[..len].iter().map(|(_, it)| *it))
+            .collect::<Vec<_>>()
+            .join(" ");
+        if Skill::find_by_name(&name).is_some() {
+            return Err(format!(
+                "Unknown skill `{item}`. Names with spaces must be quoted, e.g., `\"{name}\"`."
+            ));
+        }
+    }
+    match Skill::suggest(name) {
+        Some(suggestion) if suggestion.contains(' ') => {
+            Err(format!("Unknown skill `{name}`. Did you mean `\"{suggestion}\"`?"))
+        }
+        Some(suggestion) => Err(format!("Unknown skill `{name}`. Did you mean `{suggestion}`?")),
+        None => Err(format!(
+            "Unknown skill `{name}`. Expected a UID or the name of a skill."
+        )),
+    }
+}
+
+fn parse_setting<T>(value: &str, range: RangeInclusive<T>) -> Result<T, String>
+where
+    T: FromStr + PartialOrd + fmt::Display,
+{
+    match value.parse::<T>() {
+        Ok(value) if range.contains(&value) => Ok(value),
+        _ => Err(format!(
+            "Illegal value `{value}`. Expected a number from {} to {}.",
+            range.start(),
+            range.end()
+        )),
+    }
+}
+
+// 32 hex digits, optionally hyphenated like `030000005e040000-8e02-...`
+fn parse_uuid(text: &str) -> Option<GamepadUuid> {
+    let digits = text.replace('-', "");
+    if digits.len() != 32 || !digits.bytes().all(|it| it.is_ascii_hexdigit()) {
+        return None;
+    }
+    let mut uuid = GamepadUuid::default();
+    for (i, byte) in uuid.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&digits[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(uuid)
+}
+
+// returns the canonical name of the slot
+fn parse_key_slot(slot: &str) -> Option<&'static str> {
+    match slot.to_ascii_uppercase().as_str() {
+        "UP" | "↑" => Some("Up"),
+        "RIGHT" | "→" => Some("Right"),
+        "DOWN" | "↓" => Some("Down"),
+        "LEFT" | "←" => Some("Left"),
+        "M4" | "X1" => Some("M4"),
+        "M5" | "X2" => Some("M5"),
+        "PROFILE" => Some("Profile"),
+        "RESET" => Some("Reset"),
+        _ => None,
     }
 }
 
-// reuturns the input represented by the string and its alternative form when fault tolerance is available
-fn parse_motion(motion: &str) -> Option<Inputs> {
-    if matches!(motion, "∅" | "NONE") {
-        Some(Inputs::new())
+// the keys of a slot in [keys]. errors are empty when there're no keys at all
+fn parse_keys<T: FromStr + PartialEq>(items: &[(usize, &str)]) -> Result<Vec<T>, Vec<(usize, String)>> {
+    let mut keys = Vec::new();
+    let mut errors = Vec::new();
+    for &(column, item) in items {
+        match item.parse::<T>() {
+            Ok(key) if keys.contains(&key) => (),
+            Ok(key) => keys.push(key),
+            Err(_) => errors.push((column, format!("Unknown key `{item}`."))),
+        }
+    }
+    if keys.is_empty() || !errors.is_empty() {
+        Err(errors)
     } else {
-        let chars = motion.chars();
-        let char_count = chars.count();
-        let inputs = motion
-            .trim()
-            .chars()
-            .filter_map(|ch| ch.try_into().ok())
-            .collect::<Vec<_>>();
+        Ok(keys)
+    }
+}
+
+// `[profile: genichiro]` or `[profile: owl extends genichiro]`
+fn parse_profile_header(header: &str) -> Result<(&str, Option<&str>), String> {
+    let name = header
+        .strip_prefix('[')
+        .and_then(|it| it.strip_suffix(']'))
+        .unwrap_or(header);
+    let name = name.trim();
+    let words = name[name.find(':').map_or(0, |it| it + 1)..]
+        .split_whitespace()
+        .collect::<Vec<_>>();
+    match words.as_slice() {
+        [name] => Ok((name, None)),
+        [name, extends, parent] if extends.eq_ignore_ascii_case("extends") => Ok((name, Some(parent))),
+        [] => Err(format!("No name is specified for the profile in `{header}`.")),
+        _ => Err(format!(
+            "Illegal profile header `{header}`. Expected `[profile: NAME]` or `[profile: NAME extends PARENT]`."
+        )),
+    }
+}
+
+// the policy of a combat art or a prosthetic tool, which is told apart by the name
+#[derive(Clone, Copy)]
+enum Policy {
+    Art(ArtPolicy),
+    Tool(ToolPolicy),
+}
+
+fn parse_policy(name: &str) -> Result<Policy, String> {
+    let art = ArtPolicy::from_name(name).map(Policy::Art);
+    art.or_else(|| ToolPolicy::from_name(name).map(Policy::Tool))
+        .ok_or_else(|| {
+            format!(
+                "Unknown policy `{name}`. Expected {} for combat arts and {} for prosthetic tools.",
+                ArtPolicy::expected(),
+                ToolPolicy::expected()
+            )
+        })
+}
+
+fn parse_switch(value: &str) -> Result<bool, String> {
+    match value.to_ascii_lowercase().as_str() {
+        "on" | "true" | "yes" => Ok(true),
+        "off" | "false" | "no" => Ok(false),
+        _ => Err(format!("Illegal value `{value}`. Expected `on` or `off`.")),
+    }
+}
+
+// gamepad buttons that skills can be bound to directly
+const BUTTON_BINDINGS: [Button; 10] = [
+    Button::L3,
+    Button::R3,
+    Button::DPadUp,
+    Button::DPadRight,
+    Button::DPadDown,
+    Button::DPadLeft,
+    Button::Paddle1,
+    Button::Paddle2,
+    Button::Paddle3,
+    Button::Paddle4,
+];
+
+// returns `Ok(None)` when the item is not a binding at all but the name of the skill
+// errors come with the offset (in characters) of the problematic part of the item
+fn parse_binding(item: &str) -> Result<Option<Binding>, (usize, String)> {
+    match item.to_ascii_uppercase().as_str() {
+        "X1" | "M4" => Ok(Some(Binding::X1)),
+        "X2" | "M5" => Ok(Some(Binding::X2)),
+        "⛉" | "BLOCK" => Ok(Some(Binding::Block)),
+        // the other buttons are taken by the game. only their canonical names are accepted
+        // so that skill names ending with words like `Cross` are not mistaken for buttons
+        name => match BUTTON_BINDINGS.iter().find(|it| it.to_string() == name) {
+            Some(button) => Ok(Some(Binding::Button(*button))),
+            None => parse_motion(item).map(|inputs| inputs.map(Binding::Motion)),
+        },
+    }
+}
+
+// whether the lone word after a UID is rather a (misspelled) name than a binding
+fn looks_like_name(item: &str) -> bool {
+    item.chars().count() > Inputs::CAP as usize || Skill::suggest(item).is_some()
+}
+
+fn parse_motion(motion: &str) -> Result<Option<Inputs>, (usize, String)> {
+    match motion.parse::<Inputs>() {
+        Ok(inputs) => Ok(Some(inputs)),
         // the last element of the line may not be the inputs but rather the name of the combat arts
-        if inputs.len() != char_count {
-            return None;
+        // it's only a typo when it's mixed with glyphs or digits that never appear in names
+        Err(ParseInputsError::UnknownToken { offset, token })
+            if motion.chars().any(|ch| {
+                ch.is_ascii_digit() || matches!(ch, '↑' | '↗' | '→' | '↘' | '↓' | '↙' | '←' | '↖' | '∅' | '⛉')
+            }) =>
+        {
+            Err((offset, format!("Unknown token `{token}` in `{motion}`.")))
         }
-        Some(inputs.into_iter().take(3).collect::<Inputs>())
+        Err(ParseInputsError::TooLong { len }) => {
+            let message = format!(
+                "`{motion}` has {len} inputs while at most {} are supported.",
+                Inputs::CAP
+            );
+            Err((Inputs::CAP as usize, message))
+        }
+        Err(_) => Ok(None),
     }
 }
 
-#[allow(unused)]
-fn possible_altenrnatives(mut inputs: Inputs) -> Vec<Inputs> {
+pub fn possible_altenrnatives(mut inputs: Inputs, tolerances: Tolerances) -> Vec<Inputs> {
     if inputs.len() == 2 {
-        // fault tolerance for keyboards
-        // example: if ←→ is used while →← is not, treat →← as ←→ so that players can press A and D at the same time
         let mut possible_inputs = Vec::new();
-        possible_inputs.push(inputs.rev());
+        if tolerances.contains(Tolerance::Rev) {
+            // fault tolerance for keyboards
+            // example: if ←→ is used while →← is not, treat →← as ←→ so that players can press A and D at the same time
+            // keys pressed together come in the order of ↑→↓← under `socd  both`
+            possible_inputs.push(inputs.rev());
+        }
         let tail = inputs.pop().unwrap();
         let head = inputs.pop().unwrap();
-        if tail == head {
+        if tail == head && tolerances.contains(Tolerance::Smash) {
             // button smash
             possible_inputs.push(Inputs::from([tail, tail, tail]));
-        } else if tail == head.opposite() {
+        } else if tail == head.opposite() && tolerances.contains(Tolerance::Semicircle) {
             // semicircle, for gamepads
             possible_inputs.push(Inputs::from([head, tail.rotate(), tail]));
             possible_inputs.push(Inputs::from([head, head.rotate(), tail]));
         }
         possible_inputs
-    } else if inputs == [Left, Down, Right].into() {
+    } else if inputs == [Left, Down, Right].into() && tolerances.contains(Tolerance::Shuffle) {
         vec![
             Inputs::from([Left, Right, Down]),
             Inputs::from([Right, Left, Down]),
@@ -166,6 +2487,7 @@ fn possible_altenrnatives(mut inputs: Inputs) -> Vec<Inputs> {
     }
 }
 
+#[cfg(windows)]
 #[allow(unused)]
 fn get_item_name(uid: UID) -> Option<String> {
     let p = game::get_item_name(game::msg_repo(), uid);
@@ -179,7 +2501,29 @@ fn get_item_name(uid: UID) -> Option<String> {
 
 #[cfg(test)]
 mod test {
-    use crate::{config::Config, input::Input::*};
+    use crate::{
+        config::{
+            Arbitration, ArtPolicy, ArtSettings, Binding, Calibration, Config, DeadzoneShape, Diagnostic, Document,
+            MotionSource, MotionSources, Node, Rollback, Settings, Severity, Socd, Tolerance, Tolerances, ToolPolicy,
+            UID, possible_altenrnatives,
+        },
+        frame::Frames,
+        input::{Input::*, InputBuffer, Inputs, Notation, Stick},
+        key::{Button, Key, Trigger},
+    };
+
+    // where the diagnostics are and how severe they are
+    fn positions(diagnostics: &[Diagnostic]) -> Vec<(usize, usize, Severity)> {
+        diagnostics.iter().map(|it| (it.line, it.column, it.severity)).collect()
+    }
+
+    // where the diagnostics are and what they say
+    fn messages(diagnostics: &[Diagnostic]) -> Vec<(usize, usize, &str)> {
+        diagnostics
+            .iter()
+            .map(|it| (it.line, it.column, it.message.as_str()))
+            .collect()
+    }
 
     #[test]
     fn test_load() {
@@ -194,16 +2538,1078 @@ mod test {
             ";
         let config = Config::from(raw);
         // default
-        assert_eq!(config.arts.get([]), Some(7100));
-        assert_eq!(config.tools.get_or_default([]), [70000, 70100]);
+        assert_eq!(config.base.arts.get_or_default([]), [7100]);
+        assert_eq!(config.base.tools.get_or_default([]), [70000, 70100]);
         // inputs
-        assert_eq!(config.arts.get([Left, Right]), Some(5600));
-        assert_eq!(config.arts.get([Right, Left]), Some(7200));
+        assert_eq!(config.base.arts.get_or_default([Left, Right]), [5600]);
+        assert_eq!(config.base.arts.get_or_default([Right, Left]), [7200]);
         // rev tolerance
-        assert_eq!(config.tools.get_or_default([Left, Right]), &[74000]);
-        assert_eq!(config.tools.get_or_default([Right, Left]), &[74000]);
+        assert_eq!(config.base.tools.get_or_default([Left, Right]), &[74000]);
+        assert_eq!(config.base.tools.get_or_default([Right, Left]), &[74000]);
         // semicircle tolerance
-        assert_eq!(config.arts.get([Left, Down, Right]), Some(5600));
-        assert_eq!(config.arts.get([Right, Down, Left]), Some(7200));
+        assert_eq!(config.base.arts.get_or_default([Left, Down, Right]), [5600]);
+        assert_eq!(config.base.arts.get_or_default([Right, Down, Left]), [7200]);
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let raw = "
+7100  Ichimonji: Double           ∅
+7600  Shadowfall                  ↑↑
+5400  Dragon Flash                ↑↑
+7600  Shadowfall                  ↑↑
+5200  Nightjar Slash              ↑x
+5500  Ashina Cross                ↓↓↓↓↓↓↓↓↓
+7300  Empowered Mortal Draw       ⛉
+300   Out of Range                ←
+71OO  Typo                        →
+78400 Leaping Flame               ↑↑
+78400 Leaping Flame               ↑↑
+5300  Ichimonji
+5300  Ichimonji                   UUX
+5100  fwd
+5900  Praying Strike
+";
+        let (config, diagnostics) = Config::parse(raw);
+        assert_eq!(
+            positions(&diagnostics),
+            [
+                (5, 35, Severity::Warning),
+                (6, 36, Severity::Error),
+                (7, 43, Severity::Error),
+                (8, 35, Severity::Error),
+                (9, 1, Severity::Error),
+                (10, 1, Severity::Error),
+                (12, 35, Severity::Warning),
+                // lines without bindings are reported, so are misspelled bindings
+                (13, 1, Severity::Warning),
+                (14, 37, Severity::Error),
+                (15, 7, Severity::Error),
+                (16, 1, Severity::Warning),
+            ]
+        );
+        // combat arts on the same sequence are picked from in order
+        assert_eq!(config.base.arts.get_or_default([Up, Up]), [7600, 5400]);
+        // no truncation
+        assert_eq!(config.base.arts.get([Down; 8]), None);
+        assert_eq!(config.base.tools.get_or_default([Up, Up]), [78400]);
+    }
+
+    #[test]
+    fn test_keys() {
+        let raw = "
+[keys]
+Up     SC:0x11
+Right  RIGHT  L
+Left   J  Q
+M4     F13
+Down   ;
+Down   Foo
+Down   ;  /
+Jump   SPACE
+[skills]
+7600  Shadowfall  ↑↑
+[unknown]
+7100  Ichimonji: Double  ∅
+[keys]
+Reset  R  SELECT
+";
+        let (config, diagnostics) = Config::parse(raw);
+        assert_eq!(
+            positions(&diagnostics),
+            [
+                (8, 8, Severity::Error),
+                (9, 1, Severity::Warning),
+                (10, 1, Severity::Error),
+                (13, 1, Severity::Error),
+            ]
+        );
+        let keys = &config.keys;
+        assert_eq!(keys.up, [Key::Scancode(0x11)]);
+        assert_eq!(keys.right, [Key::Virtual(0x27), Key::Virtual(b'L' as u16)]);
+        assert_eq!(keys.down, [Key::Virtual(0xBA), Key::Virtual(0xBF)]);
+        assert_eq!(keys.left, [Key::Virtual(b'J' as u16), Key::Virtual(b'Q' as u16)]);
+        assert_eq!(keys.x1, [Trigger::Key(Key::Virtual(0x7C))]);
+        assert_eq!(keys.x2, [Trigger::Key(Key::XBUTTON2)]);
+        assert_eq!(
+            keys.reset,
+            [Trigger::Key(Key::Virtual(b'R' as u16)), Trigger::Button(Button::Select)]
+        );
+        // sections
+        assert_eq!(config.base.arts.get_or_default([Up, Up]), [7600]);
+        assert_eq!(config.base.arts.get([]), None);
+
+        // conflicting directions
+        let (_, diagnostics) = Config::parse("[keys]\nUp D\n");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!((diagnostics[0].line, diagnostics[0].severity), (2, Severity::Error));
+    }
+
+    #[test]
+    fn test_settings() {
+        let raw = "
+[settings]
+max_interval        20
+bounce-threshold    0.5
+ROTATE_THRESHOLD    0.95   # comment
+max_delay           0
+block_injection_duration
+attack_suppression_duration  1  2
+jump_threshold      0.5
+rollback            sticky
+sticky_rollback_countdown  30
+rollback            always
+sticky_art          yes
+";
+        let (config, diagnostics) = Config::parse(raw);
+        assert_eq!(
+            positions(&diagnostics),
+            [
+                (6, 21, Severity::Error),
+                (7, 1, Severity::Error),
+                (8, 33, Severity::Error),
+                (9, 1, Severity::Error),
+                (12, 21, Severity::Error),
+            ]
+        );
+        let settings = config.settings;
+        assert_eq!(settings.rollback, Rollback::Sticky);
+        assert!(settings.sticky_art);
+        assert_eq!(settings.rollback_countdown(), Some(Frames::standard(30)));
+        assert_eq!(settings.max_interval, Frames::standard(20));
+        assert_eq!(settings.bounce_threshold, 0.5);
+        assert_eq!(settings.rotate_threshold, 0.95);
+        // illegal values fall back to defaults
+        let default = Settings::default();
+        assert_eq!(settings.max_delay, default.max_delay);
+        assert_eq!(settings.block_injection_duration, default.block_injection_duration);
+        assert_eq!(
+            settings.attack_suppression_duration,
+            default.attack_suppression_duration
+        );
+        // each rollback mode comes with its own countdown
+        assert_eq!(
+            default.rollback_countdown(),
+            Some(default.prosthetic_rollback_countdown)
+        );
+        let settings = Config::from("[settings]\nrollback  never").settings;
+        assert_eq!(settings.rollback_countdown(), None);
+        // opposite keys are both pressed unless specified
+        assert_eq!(default.socd, Socd::Both);
+        let settings = Config::from("[settings]\nsocd  Last_Input_Wins").settings;
+        assert_eq!(settings.socd, Socd::LastInputWins);
+        let (config, diagnostics) = Config::parse("[settings]\nsocd  cancel");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(config.settings.socd, Socd::Both);
+    }
+
+    #[test]
+    fn test_motion_sources() {
+        let sources = Settings::default().gamepad_motion_source;
+        assert!(sources.contains(MotionSource::LeftStick));
+        assert!(!sources.contains(MotionSource::RightStick));
+        assert!(!sources.contains(MotionSource::DPad));
+
+        let raw = "
+[settings]
+gamepad_motion_source   Right_Stick+dpad
+right_bounce_threshold  0.8
+right_common_threshold  1.5
+";
+        let (config, diagnostics) = Config::parse(raw);
+        assert_eq!(positions(&diagnostics), [(5, 25, Severity::Error)]);
+        let settings = config.settings;
+        assert_eq!(
+            settings.gamepad_motion_source,
+            MotionSources::from([MotionSource::RightStick, MotionSource::DPad])
+        );
+        assert_eq!(settings.right_bounce_threshold, 0.8);
+        assert_eq!(
+            settings.right_common_threshold,
+            Settings::default().right_common_threshold
+        );
+        // the left stick keeps its own thresholds
+        assert_eq!(settings.bounce_threshold, Settings::default().bounce_threshold);
+
+        // D-pad buttons bound to anything also make motion inputs
+        let raw = "
+5400  Dragon Flash  DPAD_UP
+[keys]
+Reset  R  DPAD_DOWN
+[settings]
+gamepad_motion_source  left-stick+dpad
+";
+        let (_, diagnostics) = Config::parse(raw);
+        assert_eq!(
+            positions(&diagnostics),
+            [(2, 21, Severity::Warning), (4, 11, Severity::Warning)]
+        );
+        let (_, diagnostics) = Config::parse(raw.replace("+dpad", ""));
+        assert_eq!(diagnostics, []);
+
+        let settings = Config::from("[settings]\ngamepad_motion_source  none").settings;
+        assert_eq!(settings.gamepad_motion_source, MotionSources::NONE);
+        for value in ["left-stick+", "both", ""] {
+            let (config, diagnostics) = Config::parse(format!("[settings]\ngamepad_motion_source  {value}"));
+            assert_eq!(diagnostics.len(), 1, "{value}");
+            assert_eq!(
+                config.settings.gamepad_motion_source,
+                Settings::default().gamepad_motion_source
+            );
+        }
+    }
+
+    #[test]
+    fn test_names() {
+        for tool_policy in ToolPolicy::ALL {
+            assert_eq!(ToolPolicy::from_name(tool_policy.name()), Some(tool_policy));
+        }
+        // cases and `_` don't matter, and aliases are accepted
+        assert_eq!(Rollback::from_name("Previous_Slot"), Some(Rollback::PreviousSlot));
+        assert_eq!(ToolPolicy::from_name("LRU"), Some(ToolPolicy::LeastRecentlyUsed));
+        assert_eq!(Socd::from_name("last"), Some(Socd::LastInputWins));
+        assert_eq!(Socd::from_name("first"), Some(Socd::FirstInputWins));
+        assert_eq!(DeadzoneShape::from_name("circle"), None);
+        assert_eq!(ArtPolicy::expected(), "`first-owned` or `round-robin`");
+        assert_eq!(DeadzoneShape::expected(), "one of square, radial and scaled-radial");
+    }
+
+    #[test]
+    fn test_device_arbitration() {
+        assert_eq!(Settings::default().device_arbitration, Arbitration::LastActive);
+        let raw = "
+[settings]
+device_arbitration         Merged
+keyboard_activity_timeout  45
+gamepad_activity_timeout   0
+";
+        let (config, diagnostics) = Config::parse(raw);
+        assert_eq!(positions(&diagnostics), [(5, 28, Severity::Error)]);
+        let settings = config.settings;
+        assert_eq!(settings.device_arbitration, Arbitration::Merged);
+        assert_eq!(settings.keyboard_activity_timeout, Frames::standard(45));
+        assert_eq!(
+            settings.gamepad_activity_timeout,
+            Settings::default().gamepad_activity_timeout
+        );
+
+        let (config, diagnostics) = Config::parse("[settings]\ndevice_arbitration  both");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!(config.settings.device_arbitration, Arbitration::LastActive);
+    }
+
+    #[test]
+    fn test_diagonals() {
+        let config = Config::from("7300  Empowered Mortal Draw  ↓↘→\n[settings]\ndiagonals  on");
+        assert_eq!(config.base.arts.get_or_default([Down, DownRight, Right]), [7300]);
+        assert!(config.settings.diagonals);
+
+        // numpad digits for diagonals
+        let config = Config::from("7300  Empowered Mortal Draw  ↓3→\n[settings]\ndiagonals  on");
+        assert_eq!(config.base.arts.get_or_default([Down, DownRight, Right]), [7300]);
+
+        // diagonals are off unless they're switched on, and bound ones are warned about then
+        let config = Config::from("7300  Empowered Mortal Draw  ←↓→");
+        assert!(!config.settings.diagonals);
+        let config = Config::from("7300  Empowered Mortal Draw  ←↓→\n[settings]\ndiagonals  on\nchord_window  5");
+        assert!(config.settings.diagonals);
+        assert_eq!(config.settings.chord_window, Frames::standard(5));
+        let (config, diagnostics) = Config::parse("5400  Dragon Flash  ↑\n7300  Empowered Mortal Draw  ↓↘→");
+        assert!(!config.settings.diagonals);
+        assert_eq!(positions(&diagnostics), [(2, 30, Severity::Warning)]);
+        let (config, diagnostics) = Config::parse("7300  Empowered Mortal Draw  ↓↘→\n[settings]\ndiagonals  off");
+        assert!(!config.settings.diagonals);
+        assert_eq!(diagnostics.len(), 1);
+
+        let (_, diagnostics) = Config::parse("7300  Empowered Mortal Draw  ↓↘x");
+        assert_eq!(diagnostics.len(), 1);
+    }
+
+    #[test]
+    fn test_diagonal_sweeps() {
+        let config = Config::from(
+            "
+7300  Empowered Mortal Draw  ↓↘→
+5400  Dragon Flash           ←→
+[settings]
+diagonals  on
+",
+        );
+        // the stick passes through the diagonals of a semicircle
+        assert_eq!(
+            config.base.arts.get_or_default([Left, UpLeft, Up, UpRight, Right]),
+            [5400]
+        );
+        assert_eq!(config.base.arts.get_or_default([Left, Up, UpRight, Right]), [5400]);
+        assert_eq!(
+            config
+                .base
+                .arts
+                .get_or_default([Left, DownLeft, Down, DownRight, Right]),
+            [5400]
+        );
+        // while what's bound stays
+        assert_eq!(config.base.arts.get_or_default([Down, DownRight, Right]), [7300]);
+        assert_eq!(config.longest_motion(), 5);
+
+        // the buffer is long enough for them
+        let mut buffer = InputBuffer::new(config.settings, config.longest_motion());
+        let mut inputs = Inputs::new();
+        for (x, y) in [(-1.0, 0.0), (-1.0, 1.0), (0.0, 1.0), (1.0, 1.0), (1.0, 0.0)] {
+            inputs = buffer.update_stick(Stick::Left, x, y);
+        }
+        assert_eq!(config.base.arts.get_or_default(inputs), [5400]);
+    }
+
+    #[test]
+    fn test_numpad_notation() {
+        let config = Config::from(
+            "
+7300  Empowered Mortal Draw  236
+5400  Dragon Flash           623
+7100  Ichimonji: Double      5
+73000 Loaded Axe             22
+",
+        );
+        assert_eq!(config.notation, Notation::Numpad);
+        assert_eq!(config.base.arts.get_or_default([Down, DownRight, Right]), [7300]);
+        assert_eq!(config.base.arts.get_or_default([Right, Down, DownRight]), [5400]);
+        assert_eq!(config.base.arts.get_or_default([]), [7100]);
+        assert_eq!(config.base.tools.get_or_default([Down, Down]), [73000]);
+
+        let (_, diagnostics) = Config::parse("7300  Empowered Mortal Draw  2365");
+        assert_eq!(diagnostics.len(), 1);
+        assert_eq!((diagnostics[0].column, diagnostics[0].severity), (33, Severity::Error));
+
+        assert_eq!(
+            Config::from("7300  Empowered Mortal Draw  ↓↘→").notation,
+            Notation::Arrows
+        );
+    }
+
+    #[test]
+    fn test_tolerances() {
+        // every sequence but the bound one that leads to the art
+        fn alternatives(config: &Config, art: UID) -> Vec<String> {
+            let mut alternatives = config
+                .base
+                .arts
+                .iter()
+                .filter(|(inputs, it)| **it == [art] && !config.base.tolerances.contains_key(inputs))
+                .map(|(inputs, _)| inputs.to_string())
+                .collect::<Vec<_>>();
+            alternatives.sort();
+            alternatives
+        }
+
+        let raw = "
+5100  Whirlwind Slash        ←←
+7700  Sakura Dance           →→  +smash
+5600  Floating Passage       ←→  !strict
+5400  Dragon Flash           ↓↑  !strict  +rev
+6100  One Mind               ↗↙
+7300  Empowered Mortal Draw  ←↓→  -shuffle
+7600  Shadowfall             ↑↑  +SMASH  -smash
+[settings]
+smash_tolerance  off
+diagonals  on
+";
+        let (config, diagnostics) = Config::parse(raw);
+        assert_eq!(diagnostics, []);
+        assert_eq!(alternatives(&config, 5100), [] as [&str; 0]);
+        assert_eq!(alternatives(&config, 7700), ["→→→"]);
+        assert_eq!(alternatives(&config, 5600), [] as [&str; 0]);
+        assert_eq!(alternatives(&config, 5400), ["↑↓"]);
+        assert_eq!(alternatives(&config, 6100), ["↗↖↙", "↗↘↙", "↙↗"]);
+        // no shuffles, but the stick still passes through the diagonals
+        assert_eq!(alternatives(&config, 7300), ["←↓↘→", "←↙↓→", "←↙↓↘→"]);
+        assert_eq!(alternatives(&config, 7600), [] as [&str; 0]);
+
+        // the rules themselves
+        let all = Tolerances::ALL;
+        assert_eq!(
+            possible_altenrnatives(Inputs::from([Left, Right]), all),
+            [
+                Inputs::from([Right, Left]),
+                Inputs::from([Left, Down, Right]),
+                Inputs::from([Left, Up, Right]),
+            ]
+        );
+        assert_eq!(
+            possible_altenrnatives(Inputs::from([Left, Right]), Tolerances::from([Tolerance::Semicircle])),
+            [Inputs::from([Left, Down, Right]), Inputs::from([Left, Up, Right])]
+        );
+        assert_eq!(
+            possible_altenrnatives(Inputs::from([Up, Up]), all),
+            [Inputs::from([Up, Up]), Inputs::from([Up, Up, Up])]
+        );
+        assert_eq!(
+            possible_altenrnatives(Inputs::from([Up, Right]), all),
+            [Inputs::from([Right, Up])]
+        );
+        assert_eq!(possible_altenrnatives(Inputs::from([Left, Down, Right]), all).len(), 5);
+        assert_eq!(
+            possible_altenrnatives(Inputs::from([Left, Down, Right]), Tolerances::NONE),
+            []
+        );
+
+        // mistakes
+        let raw = "
+5600  Floating Passage       ←→  +reverse
+74000 Mist Raven             ⛉   !strict
+5300  Ichimonji                  +rev
+7100  Ichimonji: Double      ∅   +rev
+7500  Praying Strikes - Exorcism  →
+";
+        let (config, diagnostics) = Config::parse(raw);
+        assert_eq!(
+            positions(&diagnostics),
+            [
+                (2, 34, Severity::Error),
+                (3, 34, Severity::Warning),
+                (4, 34, Severity::Warning),
+                (5, 34, Severity::Warning),
+            ]
+        );
+        assert_eq!(config.base.arts.get([Left, Right]), None);
+        assert_eq!(config.base.arts.get_or_default([Right]), [7500]);
+    }
+
+    #[test]
+    fn test_document() {
+        for src in [
+            include_str!("../res/battle_instinct.cfg"),
+            include_str!("../res/battle_instinct_zh.cfg"),
+            "",
+            "\n\n",
+            "\t7100  Ichimonji: Double\t∅ \t# comment \r\n[ keys ]  \r\nUp W\r\n",
+            "5600 Floating Passage ←→ !strict +rev#not a comment",
+        ] {
+            assert_eq!(Document::parse(src).to_string(), src);
+        }
+
+        let document = Document::parse("5300  Ichimonji\n7600  Shadowfall  ↑↑  +rev  # comment\n[keys]\nUp  W");
+        let texts = |node: &Node| match node {
+            Node::Skill {
+                skill,
+                name,
+                binding,
+                modifiers,
+                ..
+            } => (
+                skill.text.clone(),
+                name.iter().map(|it| it.text.clone()).collect::<Vec<_>>(),
+                binding.as_ref().map(|it| it.text.clone()),
+                modifiers.iter().map(|it| it.text.clone()).collect::<Vec<_>>(),
+            ),
+            _ => panic!("{node:?} is not a skill"),
+        };
+        assert_eq!(
+            texts(&document.lines[0].node),
+            ("5300".to_string(), vec!["Ichimonji".to_string()], None, vec![])
+        );
+        assert_eq!(
+            texts(&document.lines[1].node),
+            (
+                "7600".to_string(),
+                vec!["Shadowfall".to_string()],
+                Some("↑↑".to_string()),
+                vec!["+rev".to_string()]
+            )
+        );
+        assert_eq!(document.lines[1].comment.as_ref().unwrap().text, "# comment");
+        assert!(matches!(document.lines[2].node, Node::Section(_)));
+        assert!(matches!(document.lines[3].node, Node::Entry { .. }));
+    }
+
+    #[test]
+    fn test_format() {
+        let src = "  # header comment
+7100 Ichimonji: Double NONE # default
+70500   Lazulite Shuriken  bLoCk
+5600 Floating Passage lr !STRICT +Rev
+5400 Dragon Flash 28
+\t5300 Ichimonji
+74000 Mist Raven x1
+7300 Empowered Mortal Draw ↓x→
+[ KEYS ]
+up w  sc:17
+x1 xbutton1 # side
+[Settings]
+Max-Interval 20 # longer
+rev_tolerance OFF
+";
+        let formatted = "# header comment
+7100  Ichimonji: Double      ∅  # default
+70500 Lazulite Shuriken      ⛉
+5600  Floating Passage       ←→  !strict  +rev
+5400  Dragon Flash           28
+5300  Ichimonji
+74000 Mist Raven             M4
+7300  Empowered Mortal Draw  ↓x→
+[keys]
+Up  W  SC:0x11
+M4  XBUTTON1  # side
+[settings]
+max_interval   20  # longer
+rev_tolerance  off
+";
+        let mut document = Document::parse(src);
+        document.format();
+        assert_eq!(document.to_string(), formatted);
+        // formatting is idempotent and keeps the meaning
+        let mut document = Document::parse(formatted);
+        document.format();
+        assert_eq!(document.to_string(), formatted);
+        assert_eq!(
+            Config::from(src).base.arts.iter().count(),
+            Config::from(formatted).base.arts.iter().count()
+        );
+        // the shipped configs are already formatted
+        for src in [
+            include_str!("../res/battle_instinct.cfg"),
+            include_str!("../res/battle_instinct_zh.cfg"),
+        ] {
+            let mut document = Document::parse(src);
+            document.format();
+            assert_eq!(document.to_string(), src);
+        }
+    }
+
+    #[test]
+    fn test_unknown_skills() {
+        let (config, diagnostics) = Config::parse("7650  Typo  ↑↑\n70050  Typo  ↑↑\n7600  Shadowfall  ↑↑");
+        assert_eq!(
+            positions(&diagnostics),
+            [(1, 1, Severity::Warning), (2, 1, Severity::Warning)]
+        );
+        // unknown skills are still bound in case the catalogue misses some
+        assert_eq!(config.base.arts.get_or_default([Up, Up]), [7650, 7600]);
+        assert_eq!(config.base.tools.get_or_default([Up, Up]), [70050]);
+    }
+
+    #[test]
+    fn test_skill_names() {
+        let raw = r#"
+Shadowfall                    ↑↑
+"Spiral Spear"                ↑↑
+"Loaded Spear - Cleave Type"  ↑↑   # comment
+ichimonji:double              ∅
+秘传·不死斩                   ←↓→
+"Mist Raven" Whatever         M4
+5200                          ↑
+Shadowfal                     ↓↓
+Spiral Spear                  ↓↓
+"Spiral Spear                 ↓↓
+Nothing                       ↓↓
+"#;
+        let (config, diagnostics) = Config::parse(raw);
+        assert_eq!(config.base.arts.get_or_default([Up, Up]), [7600]);
+        assert_eq!(config.base.tools.get_or_default([Up, Up]), [78300, 78200]);
+        assert_eq!(config.base.arts.get_or_default([]), [7100]);
+        assert_eq!(config.base.arts.get_or_default([Left, Down, Right]), [7300]);
+        assert_eq!(config.base.tools_on_x1, [74000]);
+        assert_eq!(config.base.arts.get_or_default([Up]), [5200]);
+        assert_eq!(config.base.arts.get([Down, Down]), None);
+        assert_eq!(
+            messages(&diagnostics),
+            [
+                (9, 1, "Unknown skill `Shadowfal`. Did you mean `Shadowfall`?"),
+                (
+                    10,
+                    1,
+                    "Unknown skill `Spiral`. Names with spaces must be quoted, e.g., `\"Spiral Spear\"`."
+                ),
+                (11, 1, "Unclosed quote in `\"Spiral`."),
+                (12, 1, "Unknown skill `Nothing`. Expected a UID or the name of a skill."),
+            ]
+        );
+
+        // quoted names stay in one piece when formatted
+        let mut document = Document::parse("\"Spiral Spear\" ↑↑\n7600 Shadowfall ↑↑");
+        document.format();
+        assert_eq!(
+            document.to_string(),
+            "\"Spiral Spear\"             ↑↑\n7600           Shadowfall  ↑↑"
+        );
+    }
+
+    #[test]
+    fn test_long_motions() {
+        let config = Config::from("5300  Ichimonji  ↑↑");
+        assert_eq!(config.longest_motion(), 3);
+
+        let config = Config::from("7300  Empowered Mortal Draw  ↓→→↓→→");
+        assert_eq!(config.longest_motion(), 6);
+        assert_eq!(
+            config
+                .base
+                .arts
+                .get_or_default([Down, Right, Right, Down, Right, Right]),
+            [7300]
+        );
+        assert_eq!(config.base.arts.get([Down, Right, Right]), None);
+    }
+
+    #[test]
+    fn test_profiles() {
+        let raw = "
+7100  Ichimonji: Double  ∅
+7600  Shadowfall         ↑↑  !strict
+70000 Loaded Shuriken    ∅
+74000 Mist Raven         M4
+[profile: genichiro]
+5200  Nightjar Slash     ↑↑
+78000 Loaded Spear       ↑↑
+[profile: owl extends genichiro]
+7300  Empowered Mortal Draw  ↓→→↓→→
+76000 Loaded Umbrella        M4
+[profile: genichiro]
+[profile: ape extends monkey]
+5300  Ichimonji  ↑↑
+[profile: default]
+[skills]
+5600  Floating Passage  ←→
+[keys]
+Profile  F5  Select
+";
+        let (config, diagnostics) = Config::parse(raw);
+        assert_eq!(
+            messages(&diagnostics),
+            [
+                (12, 1, "Profile `genichiro` is already declared on line 6."),
+                (
+                    13,
+                    1,
+                    "Unknown profile `monkey`. Profiles can only extend the ones declared before them."
+                ),
+                (15, 1, "`default` is reserved for the bindings outside of profiles."),
+            ]
+        );
+        let names = config.iter_profiles().map(|it| it.name.as_str()).collect::<Vec<_>>();
+        assert_eq!(names, ["default", "genichiro", "owl"]);
+        assert_eq!(
+            config.keys.profile,
+            [Trigger::Key(Key::Virtual(0x74)), Trigger::Button(Button::Select)]
+        );
+
+        // the base
+        let base = config.profile(0);
+        assert_eq!(base.arts.get_or_default([Up, Up]), [7600]);
+        assert_eq!(base.arts.get([Up, Up, Up]), None);
+        assert_eq!(base.arts.get_or_default([Left, Right]), [5600]);
+        assert_eq!(base.tools.get_or_default([Up, Up]), []);
+        // overrides and inherits, including the bindings after the profile
+        let genichiro = config.profile(1);
+        assert_eq!(genichiro.arts.get_or_default([Up, Up]), [5200]);
+        assert_eq!(genichiro.arts.get([Up, Up, Up]), None);
+        assert_eq!(genichiro.arts.get_or_default([]), [7100]);
+        assert_eq!(genichiro.arts.get_or_default([Left, Right]), [5600]);
+        assert_eq!(genichiro.tools.get_or_default([Up, Up]), [78000]);
+        assert_eq!(genichiro.tools_on_x1, [74000]);
+        // inherits from another profile
+        let owl = config.profile(2);
+        assert_eq!(owl.arts.get_or_default([Up, Up]), [5200]);
+        assert_eq!(
+            owl.arts.get_or_default([Down, Right, Right, Down, Right, Right]),
+            [7300]
+        );
+        assert_eq!(owl.tools.get_or_default([]), [70000]);
+        assert_eq!(owl.tools_on_x1, [76000]);
+        assert_eq!(config.longest_motion(), 6);
+        // out of range
+        assert_eq!(config.profile(3).name, "default");
+
+        // profiles are formatted as skills
+        let mut document =
+            Document::parse("[ Profile:  owl  EXTENDS  genichiro ]\n7600 Shadowfall ↑↑\n[keys]\nprofile f5 r3");
+        document.format();
+        assert_eq!(
+            document.to_string(),
+            "[profile: owl extends genichiro]\n7600 Shadowfall  ↑↑\n[keys]\nProfile  F5  R3"
+        );
+    }
+
+    #[test]
+    fn test_fallbacks() {
+        let raw = "
+7700  Sakura Dance  →→ > 7200 > 5600
+70000 Loaded Shuriken  ∅ > 5300
+7600  Shadowfall  ↑↑ > \"Nightjar Slash\"
+[fallbacks]
+\"Spiral Cloud Passage\"  >  5600
+7200  >  5100
+5600  >  7700
+5300  >  7100
+5100  >  70000
+5100  >
+5100  5200
+5100
+5100  >  Nightjar Slash
+";
+        let (config, diagnostics) = Config::parse(raw);
+        assert_eq!(
+            messages(&diagnostics),
+            [
+                (3, 26, "Fallbacks only apply to combat arts."),
+                (
+                    7,
+                    10,
+                    "Spiral Cloud Passage (7200) already falls back to Floating Passage (5600) on line 2. \
+                    Whirlwind Slash (5100) is ignored."
+                ),
+                (
+                    8,
+                    10,
+                    "Fallbacks can not form a cycle: Floating Passage (5600) > Sakura Dance (7700) > \
+                    Spiral Cloud Passage (7200) > Floating Passage (5600)."
+                ),
+                (
+                    9,
+                    10,
+                    "Fallbacks can not form a cycle: Ichimonji (5300) > Ichimonji: Double (7100) > Ichimonji (5300)."
+                ),
+                (
+                    10,
+                    10,
+                    "Loaded Shuriken (70000) is not a combat art. Fallbacks only apply to combat arts."
+                ),
+                (11, 7, "Expected a combat art after `>`."),
+                (12, 7, "Unexpected token `5200`. Expected `>`."),
+                (13, 1, "No fallback is specified for Whirlwind Slash (5100)."),
+                (
+                    14,
+                    10,
+                    "Unknown skill `Nightjar`. Names with spaces must be quoted, e.g., `\"Nightjar Slash\"`."
+                ),
+            ]
+        );
+        let chain = |art: UID| config.fallback_chain(art).collect::<Vec<_>>();
+        assert_eq!(chain(7700), [7700, 7200, 5600]);
+        assert_eq!(chain(7100), [7100, 5300]);
+        assert_eq!(chain(5300), [5300]);
+        // defaults can be overridden
+        assert_eq!(chain(7600), [7600, 5200]);
+        assert_eq!(chain(7500), [7500, 5900]);
+        // bindings still take effect
+        assert_eq!(config.base.arts.get_or_default([Up, Up]), [7600]);
+        assert_eq!(config.base.tools.get_or_default([]), [70000]);
+
+        // fallbacks follow the binding
+        let mut document =
+            Document::parse("7100 Ichimonji: Double ∅  >  5300 !strict\n7600 Shadowfall ↑↑\n5300 Ichimonji > 5100");
+        document.format();
+        assert_eq!(
+            document.to_string(),
+            "7100 Ichimonji: Double  ∅ > 5300  !strict\n7600 Shadowfall         ↑↑\n5300 Ichimonji          > 5100"
+        );
+    }
+
+    #[test]
+    fn test_art_settings() {
+        let raw = "
+[arts]
+\"One Mind\"  swapout_cooldown=200
+7700  sheathed=on  block_injection_duration=20  attack_suppression=off
+5100  Sheathed=Yes
+5200  cooldown=10  sheathed  swapout_cooldown=700
+5300
+70000  sheathed=on
+[settings]
+block_injection_duration  12
+";
+        let (config, diagnostics) = Config::parse(raw);
+        assert_eq!(
+            messages(&diagnostics),
+            [
+                (
+                    6,
+                    7,
+                    "Unknown setting `cooldown`. Expected one of sheathed, swapout_cooldown, \
+                    block_injection_duration and attack_suppression."
+                ),
+                (
+                    6,
+                    20,
+                    "Unexpected token `sheathed`. Expected `NAME=VALUE`, e.g., `sheathed=on`."
+                ),
+                (6, 30, "Illegal value `700`. Expected a number from 0 to 600."),
+                (7, 1, "No setting is specified for Ichimonji (5300)."),
+                (
+                    8,
+                    1,
+                    "Loaded Shuriken (70000) is not a combat art. Only combat arts can be specified in [arts]."
+                ),
+            ]
+        );
+        let art = |sheathed, swapout_cooldown, block_injection_duration, attack_suppression| ArtSettings {
+            sheathed,
+            swapout_cooldown: Frames::standard(swapout_cooldown),
+            block_injection_duration,
+            attack_suppression,
+        };
+        // partially overridden
+        assert_eq!(config.art(6100), art(true, 200, 12, true));
+        assert_eq!(config.art(7700), art(true, 60, 20, false));
+        assert_eq!(config.art(5100), art(true, 40, 12, true));
+        // defaults
+        assert_eq!(config.art(5500), art(true, 75, 12, true));
+        assert_eq!(config.art(5200), art(false, 40, 12, true));
+        assert_eq!(Config::from("").art(7600), art(false, 40, 10, true));
+
+        let mut document = Document::parse("[Arts]\n7700 Sheathed=YES swapout-cooldown=60");
+        document.format();
+        assert_eq!(document.to_string(), "[arts]\n7700  sheathed=on  swapout_cooldown=60");
+    }
+
+    #[test]
+    fn test_policies() {
+        let raw = "
+7600  Shadowfall      ↑↑  @first-owned
+5200  Nightjar Slash  ↑↑
+7100  Ichimonji: Double  ∅
+5300  Ichimonji          ∅
+5400  Dragon Flash    ←→  @round-robin
+5600  Floating Passage  ←→  @First_Owned
+5500  Ashina Cross    ↓↓  @random
+78400 Leaping Flame   ↑↑  @round-robin
+5900  Praying Strikes  @round-robin
+78300 Spiral Spear    ↑↑  @cycle
+78000 Loaded Spear    ↑↑
+76000 Loaded Umbrella  ⛉  @lru
+74000 Mist Raven       M4
+7700  Sakura Dance    →→  @strict-order
+[profile: genichiro]
+7200  Spiral Clound Passage  ↑↑
+78100 Loaded Spear Thrust Type  M4  @equipped_first
+[settings]
+art_policy   round-robin
+tool_policy  strict-order
+";
+        let (config, diagnostics) = Config::parse(raw);
+        assert_eq!(
+            messages(&diagnostics),
+            [
+                (
+                    7,
+                    29,
+                    "The policy of `←→` is already specified as `@round-robin` on line 6 and is overridden."
+                ),
+                (
+                    8,
+                    27,
+                    "Unknown policy `random`. Expected `first-owned` or `round-robin` for combat arts \
+                    and one of equipped-first, strict-order, cycle and least-recently-used for prosthetic tools."
+                ),
+                (9, 27, "`@round-robin` only applies to combat arts."),
+                (
+                    10,
+                    24,
+                    "`@round-robin` has no effect since the skill is not bound to any input."
+                ),
+                (15, 27, "`@strict-order` only applies to prosthetic tools."),
+            ]
+        );
+        let base = &config.base;
+        assert_eq!(base.arts.get_or_default([Up, Up]), [7600, 5200]);
+        assert_eq!(
+            base.art_policies[&Binding::Motion([Up, Up].into())],
+            ArtPolicy::FirstOwned
+        );
+        assert_eq!(base.arts.get_or_default([Left, Right]), [5400, 5600]);
+        assert_eq!(
+            base.art_policies[&Binding::Motion([Left, Right].into())],
+            ArtPolicy::FirstOwned
+        );
+        // the policies are shared with the alternative forms
+        assert_eq!(base.arts.get_or_default([Right, Left]), [5400, 5600]);
+        assert_eq!(
+            base.art_policies[&Binding::Motion([Right, Left].into())],
+            ArtPolicy::FirstOwned
+        );
+        assert_eq!(base.tools.get_or_default([Up, Up, Up]), [78300, 78000]);
+        assert_eq!(
+            base.tool_policies[&Binding::Motion([Up, Up, Up].into())],
+            ToolPolicy::Cycle
+        );
+        // the settings apply unless specified
+        assert_eq!(base.arts.get_or_default([]), [7100, 5300]);
+        assert_eq!(
+            base.art_policies[&Binding::Motion(Inputs::new())],
+            ArtPolicy::RoundRobin
+        );
+        assert_eq!(base.arts.get([Down, Down]), None);
+        assert_eq!(base.arts.get([Right, Right]), None);
+        assert_eq!(base.tool_policies[&Binding::Block], ToolPolicy::LeastRecentlyUsed);
+        assert_eq!(base.tool_policies[&Binding::X1], ToolPolicy::StrictOrder);
+        // profiles replace the whole list but inherit the policy
+        let genichiro = &config.profiles[0];
+        assert_eq!(genichiro.arts.get_or_default([Up, Up]), [7200]);
+        assert_eq!(
+            genichiro.art_policies[&Binding::Motion([Up, Up].into())],
+            ArtPolicy::FirstOwned
+        );
+        assert_eq!(genichiro.tools_on_x1, [78100]);
+        assert_eq!(genichiro.tool_policies[&Binding::X1], ToolPolicy::EquippedFirst);
+        assert_eq!(genichiro.tool_policies[&Binding::Block], ToolPolicy::LeastRecentlyUsed);
+        let settings = Config::from("").settings;
+        assert_eq!(settings.art_policy, ArtPolicy::FirstOwned);
+        assert_eq!(settings.tool_policy, ToolPolicy::EquippedFirst);
+
+        let mut document =
+            Document::parse("7600 Shadowfall ↑↑ @Round_Robin !strict\n5200 Nightjar Slash ↑↑\n76000 ⛉ @LRU");
+        document.format();
+        assert_eq!(
+            document.to_string(),
+            "7600  Shadowfall      ↑↑  @round-robin  !strict\n5200  Nightjar Slash  ↑↑\n76000                 ⛉   @least-recently-used"
+        );
+    }
+    #[test]
+    fn test_arts_on_extra_buttons() {
+        let raw = "
+7600  Shadowfall    M4
+5400  Dragon Flash  M4  @round-robin
+7700  Sakura Dance  M5
+7300  Empowered Mortal Draw  ⛉
+74000 Mist Raven    M4
+[profile: genichiro]
+5200  Nightjar Slash  M5
+[keys]
+M4  XBUTTON1  R3
+M5  PADDLE
+";
+        let (config, diagnostics) = Config::parse(raw);
+        assert_eq!(
+            messages(&diagnostics),
+            [
+                (5, 30, "`⛉` can only be used on prosthetic tools."),
+                (11, 5, "Unknown key `PADDLE`."),
+            ]
+        );
+        let base = &config.base;
+        assert_eq!(base.arts_on_x1, [7600, 5400]);
+        assert_eq!(base.art_policies[&Binding::X1], ArtPolicy::RoundRobin);
+        assert_eq!(base.arts_on_x2, [7700]);
+        assert_eq!(base.art_policies[&Binding::X2], ArtPolicy::FirstOwned);
+        // combat arts and prosthetic tools share the buttons
+        assert_eq!(base.tools_on_x1, [74000]);
+        // extra buttons take no motion inputs
+        assert_eq!(base.arts.iter().count(), 0);
+        let genichiro = &config.profiles[0];
+        assert_eq!(genichiro.arts_on_x1, [7600, 5400]);
+        assert_eq!(genichiro.arts_on_x2, [5200]);
+        // gamepad buttons work as the extra buttons too
+        assert_eq!(
+            config.keys.x1,
+            [Trigger::Key(Key::XBUTTON1), Trigger::Button(Button::R3)]
+        );
+        assert_eq!(config.keys.x2, [Trigger::Key(Key::XBUTTON2)]);
+    }
+    #[test]
+    fn test_gamepad_buttons() {
+        let raw = "
+7600  Shadowfall    R3
+5400  Dragon Flash  r3  @round-robin
+74000 Mist Raven    PADDLE1
+78400 Leaping Flame  PADDLE1  @cycle
+76000 Loaded Umbrella  dpad_up
+5500  Ashina Cross
+7100  Ichimonji: Double  START
+[profile: genichiro]
+5200  Nightjar Slash  DPAD_DOWN
+";
+        let (config, diagnostics) = Config::parse(raw);
+        assert_eq!(
+            positions(&diagnostics),
+            [(7, 1, Severity::Warning), (8, 26, Severity::Error)]
+        );
+        let base = &config.base;
+        assert_eq!(base.arts_on_buttons[&Button::R3], [7600, 5400]);
+        assert_eq!(base.art_policies[&Binding::Button(Button::R3)], ArtPolicy::RoundRobin);
+        assert_eq!(base.tools_on_buttons[&Button::Paddle1], [74000, 78400]);
+        assert_eq!(base.tool_policies[&Binding::Button(Button::Paddle1)], ToolPolicy::Cycle);
+        assert_eq!(base.tools_on_buttons[&Button::DPadUp], [76000]);
+        // buttons taken by the game are never bound and names are never mistaken for buttons
+        assert_eq!(base.arts_on_buttons.len(), 1);
+        assert_eq!(base.tools_on_buttons.len(), 2);
+        assert_eq!(base.arts.iter().count(), 0);
+        let genichiro = &config.profiles[0];
+        assert_eq!(genichiro.arts_on_buttons[&Button::R3], [7600, 5400]);
+        assert_eq!(genichiro.arts_on_buttons[&Button::DPadDown], [5200]);
+
+        let mut document = Document::parse("7600 Shadowfall r3\n74000 Mist Raven dpad_left");
+        document.format();
+        assert_eq!(
+            document.to_string(),
+            "7600  Shadowfall  R3\n74000 Mist Raven  DPAD_LEFT"
+        );
+    }
+
+    #[test]
+    fn test_calibrations() {
+        let raw = "
+[gamepads]
+*  left  deadzone=Scaled_Radial  inner_deadzone=0.1  drift_compensation=on
+030000005e0400008e02000000007200  left  invert_y=on  scale_x=1.2
+030000005e040000-8e02-0000-0000-7200  right  outer_deadzone=0.9  offset_x=-0.05
+030000005e04  left  invert_x=on
+*  middle  invert_x=on
+*  right  deadzone=circle  inner_deadzone=0.8  offset_y
+*  right
+*  paddles  paddle1=12  paddle5=1
+030000005e0400008e02000000007200  paddles  paddle2=13
+";
+        let (config, diagnostics) = Config::parse(raw);
+        assert_eq!(
+            positions(&diagnostics),
+            [
+                (6, 1, Severity::Error),
+                (7, 4, Severity::Error),
+                (8, 11, Severity::Error),
+                (8, 28, Severity::Error),
+                (8, 48, Severity::Error),
+                (9, 4, Severity::Error),
+                (10, 25, Severity::Error)
+            ]
+        );
+        let gamepads = &config.gamepads;
+        let left = Calibration {
+            deadzone: DeadzoneShape::ScaledRadial,
+            inner_deadzone: 0.1,
+            drift_compensation: true,
+            ..Calibration::default()
+        };
+        assert_eq!(gamepads.default.sticks, [left, Calibration::default()]);
+        assert_eq!(gamepads.default.paddles, [Some(12), None, None, None]);
+        // gamepads inherit what's specified for every gamepad
+        let uuid = [
+            0x03, 0x00, 0x00, 0x00, 0x5e, 0x04, 0x00, 0x00, 0x8e, 0x02, 0x00, 0x00, 0x00, 0x00, 0x72, 0x00,
+        ];
+        let gamepad = gamepads.get(uuid);
+        assert_eq!(gamepad.paddles, [Some(12), Some(13), None, None]);
+        assert_eq!(
+            gamepad.sticks[0],
+            Calibration {
+                invert: [false, true],
+                scale: [1.2, 1.0],
+                ..left
+            }
+        );
+        assert_eq!(
+            gamepad.sticks[1],
+            Calibration {
+                outer_deadzone: 0.9,
+                offset: (-0.05, 0.0),
+                ..Calibration::default()
+            }
+        );
+        assert_eq!(gamepads.get([0; 16]), gamepads.default);
+
+        let mut document = Document::parse("[GamePads]\n* left Deadzone=radial Invert-Y=YES");
+        document.format();
+        assert_eq!(
+            document.to_string(),
+            "[gamepads]\n*  left  deadzone=radial  invert_y=on"
+        );
     }
 }
@@ -1,12 +1,15 @@
-use std::{fmt, num::NonZero, path::Path};
-use windows::Win32::UI::Input::KeyboardAndMouse::*;
+use std::{collections::HashMap, fmt, num::NonZero, path::Path, slice};
 
 use crate::{
-    config::Config,
-    device::{Gamepad, is_key_down},
+    arbiter::Arbiter,
+    config::{ArtPolicy, Binding, Config, MotionSource, Rollback, UID},
+    device::{Gamepad, any_key_pressed_at, is_any_key_down, is_any_trigger_down, time_keys},
     frame::Frames,
     game::{self},
-    input::InputBuffer,
+    input::{InputBuffer, Inputs, Reading, Stick},
+    key::Button,
+    selection::{self, ToolSelection},
+    skill::describe,
 };
 
 //----------------------------------------------------------------------------
@@ -15,27 +18,6 @@ use crate::{
 //
 //----------------------------------------------------------------------------
 
-// MOD behavior
-const BLOCK_INJECTION_DURATION: u8 = 10;
-const ATTACK_SUPRESSION_DURATION: u8 = 2;
-const PROSTHETIC_SUPRESSION_DURATION: u8 = 2;
-const PROSTHETIC_ROLLBACK_COUNTDOWN: Frames = Frames::standard(120);
-
-// UIDs
-const ASHINA_CROSS: UID = 5500;
-const ONE_MIND: UID = 6100;
-const SAKURA_DANCE: UID = 7700;
-const ICHIMONJI: UID = 5300;
-const ICHIMONJI_DOUBLE: UID = 7100;
-const PRAYING_STRIKES: UID = 5900;
-const PRAYING_STRIKES_EXORCISM: UID = 7500;
-const SENPO_LEAPING_KICKS: UID = 5800;
-const HIGH_MONK: UID = 7400;
-const SHADOWRUSH: UID = 6000;
-const SHADOWFALL: UID = 7600;
-const MORTAL_DRAW: UID = 5700;
-const EMPOWERED_MORTAL_DRAW: UID = 7300;
-
 // action bitfields
 const ATTACK: u64 = 0x1;
 const BLOCK: u64 = 0x4;
@@ -57,11 +39,24 @@ const PROSTHETIC_SLOT_2: u8 = 4;
 
 pub struct Mod {
     config: Config,
+    // index of the active profile in `Config::iter_profiles`
+    profile: usize,
     buffer: InputBuffer,
+    arbiter: Arbiter,
     cur_art: Option<UID>,
+    // the binding the current combat art is picked from
+    cur_group: Option<Binding>,
+    // where each round-robin binding starts picking from next time
+    art_turns: HashMap<Binding, usize>,
+    // the last performed motion art and its sequence, which takes the place of the `∅` combat art under `sticky_art`
+    sticky_art: Option<(Binding, UID)>,
+    tool_selection: ToolSelection,
+    // the last used tool, which is rolled back to under `sticky`
+    sticky_tool: Option<UID>,
     blocking_last_frame: bool,
     attacking_last_frame: bool,
     using_tool_last_frame: bool,
+    switching_profile_last_frame: bool,
     swapout_countdown: Countdown,
     rollback_countdown: Countdown,
     attack_delay: u8,
@@ -75,14 +70,23 @@ pub struct Mod {
 
 impl Mod {
     pub fn new(path: impl AsRef<Path>) -> anyhow::Result<Mod> {
+        let config = Config::open(path)?;
         let modification = Mod {
-            config: Config::open(path)?,
-            gamepad: Gamepad::new()?,
-            buffer: InputBuffer::new(),
+            buffer: InputBuffer::new(config.settings, config.longest_motion()),
+            arbiter: Arbiter::default(),
+            gamepad: Gamepad::new(config.gamepads.clone())?,
+            config,
+            profile: 0,
             cur_art: None,
+            cur_group: None,
+            art_turns: HashMap::new(),
+            sticky_art: None,
+            tool_selection: ToolSelection::default(),
+            sticky_tool: None,
             blocking_last_frame: false,
             attacking_last_frame: false,
             using_tool_last_frame: false,
+            switching_profile_last_frame: false,
             swapout_countdown: Countdown::zero(),
             rollback_countdown: Countdown::zero(),
             attack_delay: 0,
@@ -95,26 +99,86 @@ impl Mod {
         Ok(modification)
     }
 
+    /// Swaps in a freshly loaded config. Input states and the bookkeeping of
+    /// ejected tools are kept so that the swap is seamless in game.
+    /// The active profile is kept as long as it's still in the config.
+    pub fn set_config(&mut self, config: Config) {
+        let name = &self.config.profile(self.profile).name;
+        self.profile = config.iter_profiles().position(|it| it.name == *name).unwrap_or(0);
+        self.reset_selection();
+        self.buffer.configure(config.settings, config.longest_motion());
+        self.gamepad.configure(config.gamepads.clone());
+        self.config = config;
+    }
+
+    // every binding starts over from its first skill
+    fn reset_selection(&mut self) {
+        self.cur_group = None;
+        self.art_turns.clear();
+        self.sticky_art = None;
+        self.tool_selection.reset();
+        self.sticky_tool = None;
+    }
+
     pub fn process_input(&mut self, input_handler: &mut game::InputHandler) {
         /***** keystates *****/
-        let w_down = is_key_down(VK_W);
-        let a_down = is_key_down(VK_A);
-        let s_down = is_key_down(VK_S);
-        let d_down = is_key_down(VK_D);
-        // bind R3/R4 to x1/x2 in the future
-        let x1_down = is_key_down(VK_XBUTTON1);
-        let x2_down = is_key_down(VK_XBUTTON2);
+        time_keys();
+        let keys = &self.config.keys;
+        let up = is_any_key_down(&keys.up);
+        let right = is_any_key_down(&keys.right);
+        let down = is_any_key_down(&keys.down);
+        let left = is_any_key_down(&keys.left);
+        let pressed_at = [&keys.up, &keys.right, &keys.down, &keys.left].map(|keys| any_key_pressed_at(keys));
+        self.gamepad.poll();
+        let x1_down = is_any_trigger_down(&keys.x1, &self.gamepad);
+        let x2_down = is_any_trigger_down(&keys.x2, &self.gamepad);
+        let switching_profile = is_any_trigger_down(&keys.profile, &self.gamepad);
+        let resetting_art = is_any_trigger_down(&keys.reset, &self.gamepad);
+
+        /***** switch to the next profile *****/
+        // only the bindings are swapped. the bookkeeping of ejected tools and previous slots is left untouched
+        // so that the tools are still reverted properly under the new profile
+        if switching_profile && !self.switching_profile_last_frame {
+            self.profile = (self.profile + 1) % self.config.iter_profiles().count();
+            self.buffer.clear();
+            self.reset_selection();
+            log::info!("Switched to profile `{}`.", self.config.profile(self.profile).name);
+        }
+        if resetting_art && self.sticky_art.take().is_some() {
+            log::info!("Reset the default combat art.");
+        }
+        let profile = self.config.profile(self.profile);
 
         /***** update the motion inputs *****/
-        let inputs = if let Some((x, y)) = self.gamepad.get_left_pos().filter(|pos| *pos != (0.0, 0.0)) {
-            self.buffer.update_joystick(x, y)
-        } else {
-            let up = w_down;
-            let right = d_down;
-            let down = s_down;
-            let left = a_down;
-            self.buffer.update_keys(up, right, down, left)
+        // the first stick off the center wins. the D-pad works like the keys
+        // the keyboard and the gamepad are then arbitrated before they reach the buffer
+        let sources = self.config.settings.gamepad_motion_source;
+        let sticks = [
+            (MotionSource::LeftStick, Stick::Left),
+            (MotionSource::RightStick, Stick::Right),
+        ];
+        let stick = sticks
+            .into_iter()
+            .filter(|(source, _)| sources.contains(*source))
+            .find_map(|(_, stick)| {
+                let pos = self.gamepad.get_stick_pos(stick).filter(|pos| *pos != (0.0, 0.0));
+                pos.map(|pos| (stick, pos))
+            });
+        // keys come with the times of their messages and the D-pad with the times of its events
+        let dpad = [Button::DPadUp, Button::DPadRight, Button::DPadDown, Button::DPadLeft];
+        let dpad_enabled = sources.contains(MotionSource::DPad);
+        let keyboard = Reading {
+            keys: [up, right, down, left],
+            pressed_at,
+            stick: None,
+        };
+        let gamepad = Reading {
+            keys: dpad.map(|button| dpad_enabled && self.gamepad.is_button_down(button)),
+            pressed_at: dpad.map(|button| self.gamepad.pressed_at(button).filter(|_| dpad_enabled)),
+            stick: stick.map(|(stick, (x, y))| (stick, x, y)),
         };
+        let reading = self.arbiter.arbitrate(&self.config.settings, keyboard, gamepad);
+        let inputs = self.buffer.update(reading);
 
         /***** parse the action bitflags *****/
         let action = &mut input_handler.action;
@@ -129,35 +193,50 @@ impl Mod {
         /***** query the desired prosthetic tool *****/
         // notice that `using_tool` is shadowed and it has a different semantics
         // than `attacking`, `blocking`, `jumping`, etc
+        let mut tools_on_buttons = profile.tools_on_buttons.iter();
+        let tools_on_button = tools_on_buttons.find(|(button, _)| self.gamepad.is_button_down(**button));
         let using_tool = using_tool
-            | (x1_down && !self.config.tools_on_x1.is_empty())
-            | (x2_down && !self.config.tools_on_x2.is_empty());
+            | (x1_down && !profile.tools_on_x1.is_empty())
+            | (x2_down && !profile.tools_on_x2.is_empty())
+            | tools_on_button.is_some();
         let used_tool_just_now = !self.using_tool_last_frame && using_tool;
 
-        let desired_tools = if used_tool_just_now {
+        let (binding, desired_tools): (Binding, &[UID]) = if used_tool_just_now {
             // equip the alternative tools only right before using them
             // so that the prosthetic slot doesn't change on plain character movement
-            self.rollback_countdown = Countdown::new(PROSTHETIC_ROLLBACK_COUNTDOWN);
-            let mut tools: &[UID] = &[];
-            if tools.is_empty() && x1_down {
-                tools = self.config.tools_on_x1;
+            let countdown = self.config.settings.rollback_countdown();
+            self.rollback_countdown = Countdown::new(countdown.unwrap_or(Frames::standard(0)));
+            let mut tools: (Binding, &[UID]) = (Binding::Motion(inputs), &[]);
+            if tools.1.is_empty() && x1_down {
+                tools = (Binding::X1, &profile.tools_on_x1);
             }
-            if tools.is_empty() && x2_down {
-                tools = self.config.tools_on_x2;
+            if tools.1.is_empty() && x2_down {
+                tools = (Binding::X2, &profile.tools_on_x2);
             }
-            if tools.is_empty() && blocking {
-                tools = self.config.tools_for_block;
+            if tools.1.is_empty()
+                && let Some((button, tools_on_button)) = tools_on_button
+            {
+                tools = (Binding::Button(*button), tools_on_button);
             }
-            if tools.is_empty() && !self.buffer.expired() {
-                tools = self.config.tools.get_or_default(inputs);
+            if tools.1.is_empty() && blocking {
+                tools = (Binding::Block, &profile.tools_for_block);
+            }
+            if tools.1.is_empty() && !self.buffer.expired() {
+                tools = (Binding::Motion(inputs), profile.tools.get_or_default(inputs));
             }
             tools
         } else if self.rollback_countdown.is_done() {
             // equip the default tool as soon as it's availble
-            let tools = self.config.tools.get_or_default([]);
-            if tools.is_empty() {
+            let rollback = self.config.settings.rollback;
+            let tools = match (rollback, &self.sticky_tool) {
+                (Rollback::Sticky, Some(tool)) => slice::from_ref(tool),
+                (Rollback::Default | Rollback::Sticky, _) => profile.tools.get_or_default([]),
+                (Rollback::PreviousSlot | Rollback::Never, _) => &[],
+            };
+            if tools.is_empty() && rollback != Rollback::Never {
                 // notice that it's possible that the player does not have any default tool configured
                 // in this case we need to rollback to the previous slot instead of the default tool
+                // which is also what `previous-slot` always does
                 if let Some(prev_slot) = self.prev_slot.take() {
                     activate_prosthetic_slot(prev_slot);
                 }
@@ -167,10 +246,10 @@ impl Mod {
                     equip_prosthetic(ejected_tool, orignal_slot);
                 }
             }
-            tools
+            (Binding::Motion(Inputs::new()), tools)
         } else {
             self.rollback_countdown.count_on(!using_tool);
-            &[]
+            (Binding::Motion(inputs), &[])
         };
 
         /***** equip the desired prosthetic tool *****/
@@ -184,58 +263,71 @@ impl Mod {
             }
         }
         if !desired_tools.is_empty() {
-            if let Some(target_slot) = desired_tools.iter().copied().filter_map(locate_prosthetic_tool).next() {
-                // when multiple tools are bind to the same inputs, use the already equiped one first
+            // when multiple tools are bind to the same input, the policy picks one of them
+            let policy = profile.tool_policies.get(&binding).copied().unwrap_or_default();
+            let inventory = Inventory {
+                ejection: self.ejection,
+            };
+            let desired_tool = self.tool_selection.pick(binding, policy, desired_tools, &inventory);
+            self.tool_selection.set_picked(desired_tool.map(|tool| (binding, tool)));
+            if let Some(target_slot) = desired_tool.and_then(locate_prosthetic_tool) {
                 if target_slot != active_slot {
                     // remembers the active slot and rollback to it later if there're not default tools configured
                     self.prev_slot.get_or_insert(active_slot);
                     activate_prosthetic_slot(target_slot);
                 }
-            } else {
-                // if none equipped, check if the ejected one is desired
-                let mut equipped = false;
-                if let Some((ejected_tool, original_slot)) = self.ejection {
-                    for tool in desired_tools.iter().copied() {
-                        if tool.get_item_id() == Some(ejected_tool) {
-                            equip_prosthetic(ejected_tool, original_slot);
-                            equipped = true;
-                            self.ejection = None;
-                            break;
-                        }
-                    }
-                }
-                // use the first one (that is owned by the player) in the list as the last resort
-                if !equipped {
-                    // replace the tool in the active slot and remembers the ejected one for later revert
-                    // notice that only the first ever ejected tool is remembered because the latter ones are
-                    // placed into the slot by the MOD but not the player. there's not point in reverting them
-                    //
-                    // placing the desired tool into some dedicated slot and activating that slot later can cause bugs.
-                    // that is because `equip_prosthetic` must happen AFTER `activate_prosthetic_slot` when they occur
-                    // within the same tick. activating the dedicated slot BEFORE placing any tool into it solves
-                    // this problem of course but now it triggers a disgusting flickering in the slot UI instead
-                    //
-                    // bugs and disgust are both unacceptable. that's why the code chooses a more complex approach:
-                    // placing tools into arbitrary active slots (thus `activate_prosthetic_slot` is no longer needed)
-                    // and keep track of the arbitrary `self.prev_slot`s and the original slots of `self.ejection`
-                    let active_tool = get_prosthetic_tool(active_slot);
-                    for tool in desired_tools.iter().copied() {
-                        if equip_prosthetic(tool, active_slot) {
-                            if let Some(active_tool) = active_tool {
-                                self.ejection.get_or_insert((active_tool, active_slot));
-                            }
-                            break;
-                        }
-                    }
+            } else if let Some((ejected_tool, original_slot)) = self.ejection
+                && desired_tool.and_then(ID::get_item_id) == Some(ejected_tool)
+            {
+                // the ejected one is put back to where it was
+                equip_prosthetic(ejected_tool, original_slot);
+                self.ejection = None;
+            } else if let Some(desired_tool) = desired_tool {
+                // replace the tool in the active slot and remembers the ejected one for later revert
+                // notice that only the first ever ejected tool is remembered because the latter ones are
+                // placed into the slot by the MOD but not the player. there's not point in reverting them
+                //
+                // placing the desired tool into some dedicated slot and activating that slot later can cause bugs.
+                // that is because `equip_prosthetic` must happen AFTER `activate_prosthetic_slot` when they occur
+                // within the same tick. activating the dedicated slot BEFORE placing any tool into it solves
+                // this problem of course but now it triggers a disgusting flickering in the slot UI instead
+                //
+                // bugs and disgust are both unacceptable. that's why the code chooses a more complex approach:
+                // placing tools into arbitrary active slots (thus `activate_prosthetic_slot` is no longer needed)
+                // and keep track of the arbitrary `self.prev_slot`s and the original slots of `self.ejection`
+                let active_tool = get_prosthetic_tool(active_slot);
+                if equip_prosthetic(desired_tool, active_slot)
+                    && let Some(active_tool) = active_tool
+                {
+                    self.ejection.get_or_insert((active_tool, active_slot));
                 }
             }
-            self.prosthetic_delay = PROSTHETIC_SUPRESSION_DURATION;
+            self.prosthetic_delay = self.config.settings.prosthetic_suppression_duration;
+        } else {
+            // uses without any bound tool move none of the bindings on
+            self.tool_selection.set_picked(None);
+        }
+
+        // `cycle` and `least-recently-used` move on only when the picked tool is actually used
+        if used_tool_just_now && let Some(tool) = self.tool_selection.use_picked() {
+            self.sticky_tool = Some(tool);
         }
 
         /***** query the desired combat art *****/
         let mut performed_block_free_art_just_now = false;
         let performed_art_just_now = blocking && attacked_just_now;
-        let desired_art = if !self.swapout_countdown.is_done() {
+        // the names are only formatted when logged
+        let arts_on_extra_buttons = if x1_down && !profile.arts_on_x1.is_empty() {
+            Some((&"M4" as &dyn fmt::Display, Binding::X1, profile.arts_on_x1.as_slice()))
+        } else if x2_down && !profile.arts_on_x2.is_empty() {
+            Some((&"M5" as &dyn fmt::Display, Binding::X2, profile.arts_on_x2.as_slice()))
+        } else {
+            let mut arts_on_buttons = profile.arts_on_buttons.iter();
+            arts_on_buttons
+                .find(|(button, _)| self.gamepad.is_button_down(**button))
+                .map(|(button, arts)| (button as &dyn fmt::Display, Binding::Button(*button), arts.as_slice()))
+        };
+        let desired_arts = if !self.swapout_countdown.is_done() {
             // fix buggy behavior of sakura dacne, ashina cross and one mind
             // One Mind has two windows for animation bugs to happen
             // one after pressing ATTACK (sheathing) and one after releasing ATTACK (drawing)
@@ -243,25 +335,45 @@ impl Mod {
             // but only start counting it down after ATTACK is released
             self.swapout_countdown.count_on(!attacking);
             None
+        } else if attacked_just_now && let Some((name, binding, arts)) = arts_on_extra_buttons {
+            // extra buttons perform their combat arts without BLOCK and motion inputs, just like the motion arts
+            log::debug!("{name} performs a combat art.");
+            performed_block_free_art_just_now = true;
+            Some((binding, arts))
         } else if attacked_just_now && !self.buffer.expired() {
             // rolling back is postponed to when BLOCK is pressed
             // only switch combat arts right before they are performed or else bugs can happen
             // for example, doing it while using Sakura Dance triggers the falling animation of High Monk
             // to cancel that unexpected animation, block/combat art need to take place
             // thus the moment of switching is delayed to when block/combat art happens
-            self.config.arts.get(inputs).inspect(|_| {
-                if inputs.meant_for_art() {
-                    performed_block_free_art_just_now = true;
-                }
-            })
+            profile
+                .arts
+                .get(inputs)
+                .map(|arts| (Binding::Motion(inputs), arts.as_slice()))
+                .inspect(|_| {
+                    if inputs.meant_for_art() {
+                        log::debug!("{} performs a combat art.", inputs.display(self.config.notation));
+                        performed_block_free_art_just_now = true;
+                    }
+                })
         } else if blocked_just_now {
             if self.buffer.expired() {
                 // when there're no recent inputs and the block button is just pressed, roll back to the default art
                 // also manually clear the input buffer so the desired art in the next few frames will still be the default art
                 self.buffer.clear();
-                self.config.arts.get([])
+                match &self.sticky_art {
+                    // the last performed motion art stays as the default one until it's reset
+                    Some((group, art)) if self.config.settings.sticky_art => Some((*group, slice::from_ref(art))),
+                    _ => profile
+                        .arts
+                        .get([])
+                        .map(|arts| (Binding::Motion(Inputs::new()), arts.as_slice())),
+                }
             } else {
-                self.config.arts.get(inputs)
+                profile
+                    .arts
+                    .get(inputs)
+                    .map(|arts| (Binding::Motion(inputs), arts.as_slice()))
             }
         } else {
             None
@@ -270,35 +382,74 @@ impl Mod {
         // if combat art switching happens too quick after performing certain combat arts
         // animation of other unrelated combat arts can be triggered
         if performed_art_just_now || performed_block_free_art_just_now && self.swapout_countdown.is_done() {
-            self.swapout_countdown = Countdown::new(self.cur_art.swapout_cooldown())
+            let cooldown = self.cur_art.map(|art| self.config.art(art).swapout_cooldown);
+            self.swapout_countdown = Countdown::new(cooldown.unwrap_or(Frames::standard(0)))
         }
 
         /***** equip the desired combat art (or its fallback version) *****/
-        if let Some(desired_art) = desired_art {
-            let mut desired_art = desired_art;
-            loop {
+        if let Some((group, arts)) = desired_arts {
+            let start = match profile.art_policies.get(&group).copied().unwrap_or_default() {
+                ArtPolicy::FirstOwned => 0,
+                ArtPolicy::RoundRobin => self.art_turns.get(&group).copied().unwrap_or_default() % arts.len(),
+            };
+            // the ones not owned are skipped and the fallbacks are only tried when none of them is owned
+            let arts = arts.iter().cycle().skip(start).take(arts.len()).copied();
+            let fallbacks = arts.clone().flat_map(|art| self.config.fallback_chain(art).skip(1));
+            let mut equipped = false;
+            for desired_art in arts.chain(fallbacks) {
                 if self.cur_art == Some(desired_art) {
+                    equipped = true;
                     break;
                 }
                 if set_combat_art(desired_art) {
+                    log::debug!("Switched to {}.", describe(desired_art));
                     self.cur_art = Some(desired_art);
-                    self.attack_delay = ATTACK_SUPRESSION_DURATION;
-                    break;
-                }
-                desired_art = match desired_art {
-                    ICHIMONJI_DOUBLE => ICHIMONJI,
-                    PRAYING_STRIKES_EXORCISM => PRAYING_STRIKES,
-                    HIGH_MONK => SENPO_LEAPING_KICKS,
-                    SHADOWFALL => SHADOWRUSH,
-                    EMPOWERED_MORTAL_DRAW => MORTAL_DRAW,
-                    _ => {
-                        break;
+                    if self.config.art(desired_art).attack_suppression {
+                        self.attack_delay = self.config.settings.attack_suppression_duration;
                     }
+                    equipped = true;
+                    break;
                 }
             }
+            // the art left equipped belongs to no group when none of the group could be equipped,
+            // so that it neither takes the turn of the group nor becomes its default one
+            self.cur_group = equipped.then_some(group);
+        }
+        // a round-robin sequence moves on to the next combat art once the current one is performed
+        if (performed_art_just_now || performed_block_free_art_just_now)
+            && let Some(group) = self.cur_group
+        {
+            let arts = match group {
+                Binding::Motion(inputs) => profile.arts.get_or_default(inputs),
+                Binding::X1 => &profile.arts_on_x1,
+                Binding::X2 => &profile.arts_on_x2,
+                Binding::Button(button) => profile
+                    .arts_on_buttons
+                    .get(&button)
+                    .map(Vec::as_slice)
+                    .unwrap_or_default(),
+                Binding::Block => &[],
+            };
+            let turn = self.art_turns.entry(group).or_default();
+            *turn = match arts.iter().position(|art| Some(*art) == self.cur_art) {
+                Some(index) => index + 1,
+                None => *turn + 1,
+            };
+            // and a motion art performed becomes the default one
+            if self.config.settings.sticky_art
+                && let Binding::Motion(inputs) = group
+                && !inputs.is_empty()
+                && let Some(art) = self.cur_art
+            {
+                self.sticky_art = Some((group, art));
+            }
         }
 
         /***** action injection *****/
+        let (sheathed, block_injection_duration) = match self.cur_art.map(|art| self.config.art(art)) {
+            Some(art) => (art.sheathed, art.block_injection_duration),
+            None => (false, self.config.settings.block_injection_duration),
+        };
         // inputs like [Up, Up] or [Down, Up] clearly means combat art usage intead of moving
         // in such cases, players can perform combat arts without pressing BLOCK,
         // because the mod injects the BLOCK action for them
@@ -309,7 +460,7 @@ impl Mod {
             if jumping || dodging {
                 // DODGE and JUMP cancel the injection because they cancel the combat art itself
                 self.injected_blocks = 0
-            } else if self.cur_art.is_sheathed() {
+            } else if sheathed {
                 // hold BLOCK for sheathing attacks as long as ATTACK is held until:
                 // 1. the player decides to hold BLOCK by themself (that usually means cancelling)
                 // 2. the player released the attack
@@ -318,7 +469,7 @@ impl Mod {
                 } else {
                     self.injected_blocks = 0;
                 }
-            } else if self.injected_blocks < BLOCK_INJECTION_DURATION {
+            } else if self.injected_blocks < block_injection_duration {
                 // inject just a few frames for other art
                 *action |= BLOCK;
                 self.injected_blocks += 1;
@@ -360,37 +511,7 @@ impl Mod {
         self.attacking_last_frame = attacking;
         self.blocking_last_frame = blocking;
         self.using_tool_last_frame = using_tool;
-    }
-}
-
-trait CombatArt: Sized {
-    fn is_sheathed(self) -> bool;
-    fn swapout_cooldown(self) -> Frames;
-}
-
-impl CombatArt for UID {
-    fn is_sheathed(self) -> bool {
-        matches!(self, ASHINA_CROSS | ONE_MIND)
-    }
-
-    fn swapout_cooldown(self) -> Frames {
-        let frames = match self {
-            ASHINA_CROSS => 75,
-            ONE_MIND => 240,
-            SAKURA_DANCE => 60,
-            _ => 40,
-        };
-        Frames::standard(frames)
-    }
-}
-
-impl CombatArt for Option<UID> {
-    fn is_sheathed(self) -> bool {
-        self.map(CombatArt::is_sheathed).unwrap_or(false)
-    }
-
-    fn swapout_cooldown(self) -> Frames {
-        self.map(CombatArt::swapout_cooldown).unwrap_or(Frames::standard(0))
+        self.switching_profile_last_frame = switching_profile;
     }
 }
 
@@ -436,9 +557,6 @@ impl Countdown {
 //
 //----------------------------------------------------------------------------
 
-/// UIDs are consistent through different save files.
-pub type UID = u32;
-
 /// When players obtain skills(combat arts/prosthetic tools), skills become items in the inventory.
 /// Thus a skill has 2 IDs: its original UID and its ID as an item in the inventory.
 /// When putting things into item slots, the latter shall be used.
@@ -561,6 +679,26 @@ fn activate_prosthetic_slot(slot: ProstheticSlot) {
     game::set_equipped_prosthetic(unknown, 0, slot.as_prosthetic_index());
 }
 
+// the prosthetic tools of the player as the selection sees them
+struct Inventory {
+    ejection: Option<(ItemID, ProstheticSlot)>,
+}
+
+impl selection::Inventory for Inventory {
+    fn is_equipped(&self, tool: UID) -> bool {
+        locate_prosthetic_tool(tool).is_some()
+    }
+
+    fn is_ejected(&self, tool: UID) -> bool {
+        self.ejection
+            .is_some_and(|(ejected_tool, _)| tool.get_item_id() == Some(ejected_tool))
+    }
+
+    fn owns(&self, tool: UID) -> bool {
+        tool.get_item_id().is_some()
+    }
+}
+
 fn game_data() -> &'static game::GameData {
     unsafe { game::game_data().as_ref().expect("`game_data` is null.") }
 }
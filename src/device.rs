@@ -1,72 +1,351 @@
-use std::{error, fmt};
-
-use gilrs::{Axis, EventType, Gilrs};
-use windows::Win32::UI::Input::KeyboardAndMouse::{GetKeyState, VIRTUAL_KEY};
-
-pub fn is_key_down(keycode: VIRTUAL_KEY) -> bool {
-    unsafe { GetKeyState(keycode.0.into()) as u16 & 0x8000 != 0 }
-}
-
-pub struct Gamepad {
-    girls: Gilrs,
-    connected: bool,
-    left_pos: (f32, f32),
-}
-
-impl Gamepad {
-    pub fn new() -> Result<Self, Error> {
-        let girls = Gilrs::new()?;
-        let connected = girls.gamepads().next().is_some();
-        let gamepad = Self {
-            girls,
-            connected,
-            left_pos: (0.0, 0.0),
-        };
-        Ok(gamepad)
-    }
-
-    pub fn get_left_pos(&mut self) -> Option<(f32, f32)> {
-        while let Some(event) = self.girls.next_event() {
-            match event.event {
-                EventType::Connected => self.connected = true,
-                EventType::Disconnected => self.connected = self.girls.gamepads().next().is_some(),
-                EventType::AxisChanged(Axis::LeftStickX, value, _code) => self.left_pos.0 = value,
-                EventType::AxisChanged(Axis::LeftStickY, value, _code) => self.left_pos.1 = value,
-                _ => (),
-            }
-        }
-        if self.connected { Some(self.left_pos) } else { None }
-    }
-}
-
-#[derive(Debug)]
-pub enum Error {
-    NotImplemented,
-    InvalidAxisToBtn,
-    Other(#[allow(unused)] Box<dyn error::Error + Send + Sync + 'static>),
-}
-
-impl error::Error for Error {}
-
-impl fmt::Display for Error {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            Error::NotImplemented => f.write_str("Gilrs does not support current platform."),
-            Error::InvalidAxisToBtn => {
-                f.write_str("Either `pressed ≤ released` or one of values is outside [0.0, 1.0] range.")
-            }
-            Error::Other(e) => e.fmt(f),
-        }
-    }
-}
-
-impl From<gilrs::Error> for Error {
-    fn from(value: gilrs::Error) -> Self {
-        match value {
-            gilrs::Error::NotImplemented(_dummy) => Self::NotImplemented,
-            gilrs::Error::InvalidAxisToBtn => Self::InvalidAxisToBtn,
-            gilrs::Error::Other(error) => Self::Other(error),
-            _ => todo!(),
-        }
-    }
-}
+use std::{
+    cell::{Cell, RefCell},
+    collections::{HashMap, HashSet},
+    error, fmt,
+    time::{Duration, SystemTime},
+};
+
+use gilrs::{Axis, EventType, GamepadId, Gilrs};
+use windows::Win32::{
+    Foundation::{HINSTANCE, LPARAM, LRESULT, WPARAM},
+    System::{SystemInformation::GetTickCount, Threading::GetCurrentThreadId},
+    UI::{
+        Input::KeyboardAndMouse::{GetKeyState, MAPVK_VSC_TO_VK_EX, MapVirtualKeyW},
+        WindowsAndMessaging::{
+            CallNextHookEx, HC_ACTION, HHOOK, MSG, PM_REMOVE, SetWindowsHookExW, WH_GETMESSAGE, WM_KEYDOWN, WM_KEYUP,
+            WM_SYSKEYDOWN, WM_SYSKEYUP,
+        },
+    },
+};
+
+use crate::{
+    config::{GamepadSettings, Gamepads},
+    input::{Stick, StickCalibrator},
+    key::{Button, Key, Trigger},
+};
+
+pub fn is_key_down(key: Key) -> bool {
+    let keycode = match key {
+        Key::Virtual(keycode) => keycode,
+        // scancodes are mapped with the current keyboard layout
+        Key::Scancode(scancode) => unsafe { MapVirtualKeyW(scancode.into(), MAPVK_VSC_TO_VK_EX) as u16 },
+    };
+    unsafe { GetKeyState(keycode.into()) as u16 & 0x8000 != 0 }
+}
+
+pub fn is_any_key_down(keys: &[Key]) -> bool {
+    keys.iter().copied().any(is_key_down)
+}
+
+thread_local! {
+    static KEYBOARD_HOOKED: Cell<bool> = const { Cell::new(false) };
+    // keys held down and when they're pressed, by both their virtual-key codes and their scancodes
+    static KEYS_PRESSED_AT: RefCell<HashMap<Key, SystemTime>> = RefCell::new(HashMap::new());
+}
+
+/// Starts timing the keys from the keyboard messages of the current thread, which should be the one that reads the
+/// keys. Keys are untimed until then, or when the messages never reach the thread
+pub fn time_keys() {
+    if KEYBOARD_HOOKED.replace(true) {
+        return;
+    }
+    let hook = unsafe {
+        SetWindowsHookExW(
+            WH_GETMESSAGE,
+            Some(on_message),
+            HINSTANCE::default(),
+            GetCurrentThreadId(),
+        )
+    };
+    match hook {
+        Ok(_) => log::info!("Timing the keys from the keyboard messages."),
+        Err(e) => log::warn!("Keys are read without timestamps: {e}"),
+    }
+}
+
+unsafe extern "system" fn on_message(code: i32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
+    if code == HC_ACTION as i32 && wparam.0 == PM_REMOVE.0 as usize {
+        let msg = unsafe { &*(lparam.0 as *const MSG) };
+        let lparam = msg.lParam.0 as u32;
+        // the scancode is in bits 16-23 and the extended flag in bit 24
+        let scancode = (lparam >> 16 & 0xFF) as u16 | if lparam & 1 << 24 != 0 { 0xE000 } else { 0 };
+        let keys = [Key::Virtual(msg.wParam.0 as u16), Key::Scancode(scancode)];
+        match msg.message {
+            // bit 30 is set when the key is already down, i.e., on auto-repeat
+            WM_KEYDOWN | WM_SYSKEYDOWN if lparam & 1 << 30 == 0 => {
+                // the message time is in the milliseconds since the system started, like `GetTickCount`
+                let age = unsafe { GetTickCount() }.wrapping_sub(msg.time);
+                let pressed_at = SystemTime::now() - Duration::from_millis(age.into());
+                KEYS_PRESSED_AT.with_borrow_mut(|it| it.extend(keys.map(|key| (key, pressed_at))));
+            }
+            WM_KEYUP | WM_SYSKEYUP => {
+                KEYS_PRESSED_AT.with_borrow_mut(|it| keys.iter().for_each(|key| _ = it.remove(key)))
+            }
+            _ => (),
+        }
+    }
+    unsafe { CallNextHookEx(HHOOK::default(), code, wparam, lparam) }
+}
+
+/// When the earliest of the keys still held is pressed. `None` when none of them is timed
+pub fn any_key_pressed_at(keys: &[Key]) -> Option<SystemTime> {
+    let pressed_at = |key: &Key| KEYS_PRESSED_AT.with_borrow(|it| it.get(key).copied());
+    keys.iter()
+        .filter(|key| is_key_down(**key))
+        .filter_map(pressed_at)
+        .min()
+}
+
+pub fn is_any_trigger_down(triggers: &[Trigger], gamepad: &Gamepad) -> bool {
+    triggers.iter().any(|trigger| match *trigger {
+        Trigger::Key(key) => is_key_down(key),
+        Trigger::Button(button) => gamepad.is_button_down(button),
+    })
+}
+
+pub struct Gamepad {
+    girls: Gilrs,
+    connected: bool,
+    settings: Gamepads,
+    // every connected gamepad keeps its own sticks so that an idle one doesn't disturb the others
+    pads: HashMap<GamepadId, Pad>,
+    // the gamepad that received the latest real input
+    id: Option<GamepadId>,
+    // calibrated positions of the sticks of that gamepad, indexed by `Stick`
+    pos: [(f32, f32); 2],
+}
+
+// the sticks and the buttons of a gamepad
+struct Pad {
+    // raw positions of the sticks, indexed by `Stick`
+    raw_pos: [(f32, f32); 2],
+    calibrators: [StickCalibrator; 2],
+    // native codes of the paddles
+    paddles: [Option<u32>; 4],
+    // buttons held down and when they're pressed, tracked from the events
+    buttons: HashMap<gilrs::Button, SystemTime>,
+    // buttons that gilrs can't name, such as back paddles, held down by their native codes
+    unknown_buttons: HashSet<u32>,
+}
+
+impl Pad {
+    fn new(settings: GamepadSettings) -> Self {
+        Self {
+            raw_pos: [(0.0, 0.0); 2],
+            calibrators: settings.sticks.map(StickCalibrator::new),
+            paddles: settings.paddles,
+            buttons: HashMap::new(),
+            unknown_buttons: HashSet::new(),
+        }
+    }
+}
+
+// how far a stick is pushed to take the gamepad into use, which drift and idle devices never reach
+const ACTIVITY_THRESHOLD: f32 = 0.5;
+
+impl Gamepad {
+    pub fn new(settings: Gamepads) -> Result<Self, Error> {
+        let girls = Gilrs::new()?;
+        let connected = girls.gamepads().next().is_some();
+        let mut gamepad = Self {
+            girls,
+            connected,
+            settings,
+            pads: HashMap::new(),
+            id: None,
+            pos: [(0.0, 0.0); 2],
+        };
+        let ids = gamepad.girls.gamepads().map(|(id, _)| id).collect::<Vec<_>>();
+        for id in ids {
+            gamepad.connect(id);
+        }
+        Ok(gamepad)
+    }
+
+    /// Swaps in the gamepad settings of a freshly loaded config. The learned drift starts over
+    pub fn configure(&mut self, settings: Gamepads) {
+        self.settings = settings;
+        for (&id, pad) in &mut self.pads {
+            let settings = self.settings.get(self.girls.gamepad(id).uuid());
+            pad.calibrators = settings.sticks.map(StickCalibrator::new);
+            pad.paddles = settings.paddles;
+        }
+    }
+
+    // starts tracking the gamepad with its settings
+    fn connect(&mut self, id: GamepadId) -> &mut Pad {
+        let gamepad = self.girls.gamepad(id);
+        self.pads.entry(id).or_insert_with(|| {
+            let uuid = gamepad.uuid().map(|it| format!("{it:02x}")).concat();
+            log::info!("Gamepad `{}` ({uuid}) is connected.", gamepad.name());
+            Pad::new(self.settings.get(gamepad.uuid()))
+        })
+    }
+
+    // only real inputs switch the gamepad in use
+    fn activate(&mut self, id: GamepadId) {
+        if self.id != Some(id) {
+            log::debug!("Gamepad `{}` is in use.", self.girls.gamepad(id).name());
+            self.id = Some(id);
+        }
+    }
+
+    /// Drains the pending events. Must be called once per frame before querying the states
+    pub fn poll(&mut self) {
+        while let Some(event) = self.girls.next_event() {
+            match event.event {
+                EventType::Connected => {
+                    self.connected = true;
+                    self.connect(event.id);
+                }
+                EventType::Disconnected => {
+                    self.pads.remove(&event.id);
+                    if self.id == Some(event.id) {
+                        self.id = None;
+                    }
+                    self.connected = self.girls.gamepads().next().is_some();
+                }
+                EventType::ButtonPressed(gilrs::Button::Unknown, code) => {
+                    // so that the paddles can be bound by their codes in `[gamepads]`
+                    log::info!("Unnamed button {} is pressed.", code.into_u32());
+                    self.connect(event.id).unknown_buttons.insert(code.into_u32());
+                    self.activate(event.id);
+                }
+                EventType::ButtonReleased(gilrs::Button::Unknown, code) => {
+                    if let Some(pad) = self.pads.get_mut(&event.id) {
+                        pad.unknown_buttons.remove(&code.into_u32());
+                    }
+                }
+                EventType::ButtonPressed(button, _code) => {
+                    self.connect(event.id).buttons.insert(button, event.time);
+                    self.activate(event.id);
+                }
+                EventType::ButtonReleased(button, _code) => {
+                    if let Some(pad) = self.pads.get_mut(&event.id) {
+                        pad.buttons.remove(&button);
+                    }
+                }
+                EventType::AxisChanged(axis, value, _code) => {
+                    let pos = &mut self.connect(event.id).raw_pos;
+                    match axis {
+                        Axis::LeftStickX => pos[0].0 = value,
+                        Axis::LeftStickY => pos[0].1 = value,
+                        Axis::RightStickX => pos[1].0 = value,
+                        Axis::RightStickY => pos[1].1 = value,
+                        _ => continue,
+                    }
+                    if value.abs() > ACTIVITY_THRESHOLD {
+                        self.activate(event.id);
+                    }
+                }
+                _ => (),
+            }
+        }
+        // calibrated every frame so that the drift is learned while the sticks rest
+        for (&id, pad) in &mut self.pads {
+            for (i, calibrator) in pad.calibrators.iter_mut().enumerate() {
+                let (x, y) = pad.raw_pos[i];
+                let pos = calibrator.calibrate(x, y);
+                if self.id == Some(id) {
+                    self.pos[i] = pos;
+                }
+            }
+        }
+        if self.id.is_none() {
+            self.pos = [(0.0, 0.0); 2];
+        }
+    }
+
+    /// The calibrated position of the stick
+    pub fn get_stick_pos(&self, stick: Stick) -> Option<(f32, f32)> {
+        if self.connected {
+            Some(self.pos[stick as usize])
+        } else {
+            None
+        }
+    }
+
+    // buttons are only read from the gamepad in use, like the sticks
+    fn pad(&self) -> Option<&Pad> {
+        self.id.and_then(|id| self.pads.get(&id))
+    }
+
+    pub fn is_button_down(&self, button: Button) -> bool {
+        let Some(pad) = self.pad() else {
+            return false;
+        };
+        match gilrs_button(button) {
+            Some(button) => pad.buttons.contains_key(&button),
+            // paddles are the unnamed buttons with the native codes specified in `[gamepads]`
+            None => {
+                let index = button as usize - Button::Paddle1 as usize;
+                pad.paddles[index].is_some_and(|code| pad.unknown_buttons.contains(&code))
+            }
+        }
+    }
+
+    /// When the held button is pressed. `None` when it's not held or the time is unknown
+    pub fn pressed_at(&self, button: Button) -> Option<SystemTime> {
+        self.pad()?.buttons.get(&gilrs_button(button)?).copied()
+    }
+}
+
+// `None` for the paddles, which gilrs can't name
+fn gilrs_button(button: Button) -> Option<gilrs::Button> {
+    let button = match button {
+        Button::South => gilrs::Button::South,
+        Button::East => gilrs::Button::East,
+        Button::North => gilrs::Button::North,
+        Button::West => gilrs::Button::West,
+        Button::C => gilrs::Button::C,
+        Button::Z => gilrs::Button::Z,
+        Button::L1 => gilrs::Button::LeftTrigger,
+        Button::L2 => gilrs::Button::LeftTrigger2,
+        Button::R1 => gilrs::Button::RightTrigger,
+        Button::R2 => gilrs::Button::RightTrigger2,
+        Button::Select => gilrs::Button::Select,
+        Button::Start => gilrs::Button::Start,
+        Button::Mode => gilrs::Button::Mode,
+        Button::L3 => gilrs::Button::LeftThumb,
+        Button::R3 => gilrs::Button::RightThumb,
+        Button::DPadUp => gilrs::Button::DPadUp,
+        Button::DPadRight => gilrs::Button::DPadRight,
+        Button::DPadDown => gilrs::Button::DPadDown,
+        Button::DPadLeft => gilrs::Button::DPadLeft,
+        Button::Paddle1 | Button::Paddle2 | Button::Paddle3 | Button::Paddle4 => return None,
+    };
+    Some(button)
+}
+
+#[derive(Debug)]
+pub enum Error {
+    NotImplemented,
+    InvalidAxisToBtn,
+    Other(#[allow(unused)] Box<dyn error::Error + Send + Sync + 'static>),
+}
+
+impl error::Error for Error {}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::NotImplemented => f.write_str("Gilrs does not support current platform."),
+            Error::InvalidAxisToBtn => {
+                f.write_str("Either `pressed ≤ released` or one of values is outside [0.0, 1.0] range.")
+            }
+            Error::Other(e) => e.fmt(f),
+        }
+    }
+}
+
+impl From<gilrs::Error> for Error {
+    fn from(value: gilrs::Error) -> Self {
+        match value {
+            gilrs::Error::NotImplemented(_dummy) => Self::NotImplemented,
+            gilrs::Error::InvalidAxisToBtn => Self::InvalidAxisToBtn,
+            gilrs::Error::Other(error) => Self::Other(error),
+            // the error is non-exhaustive and may hold a `Gilrs`, which can't be sent across threads
+            error => Self::Other(error.to_string().into()),
+        }
+    }
+}
@@ -1,11 +1,11 @@
-use std::{cell::UnsafeCell, mem, time::Instant};
+use std::{cell::UnsafeCell, time::Instant};
 
 pub const DEFAULT_FRAMERATE: u16 = 60;
 
 /// Frame count under the standard FPS as a time unit, namely, 1/60s.
 /// It can be adjusted to the current framerate
 #[repr(transparent)]
-#[derive(Clone, Copy)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct Frames(u16);
 impl Frames {
     #[inline(always)]
@@ -14,7 +14,13 @@ impl Frames {
     }
     #[inline(always)]
     pub fn as_actual(self) -> u16 {
-        self.0 * FRAMERATE.cur() / DEFAULT_FRAMERATE
+        self.at(FRAMERATE.cur())
+    }
+    /// The frame count under the framerate. Long durations under unlocked framerates saturate
+    #[inline(always)]
+    pub fn at(self, framerate: u16) -> u16 {
+        let frames = self.0 as u32 * framerate as u32 / DEFAULT_FRAMERATE as u32;
+        frames.min(u16::MAX as u32) as u16
     }
     #[allow(unused)]
     #[inline(always)]
@@ -38,7 +44,8 @@ impl Framerate {
         unsafe { self.as_mut().cur() }
     }
 
-    /// Safety: this function should only be hooked into the main tick of the game, but never
+    /// # Safety
+    /// This function should only be hooked into the main tick of the game, but never
     /// called directly from arbitrary threads
     #[inline(always)]
     pub unsafe fn tick(&self) {
@@ -46,8 +53,9 @@ impl Framerate {
     }
 
     #[inline(always)]
+    #[allow(clippy::mut_from_ref)]
     unsafe fn as_mut(&self) -> &mut FramerateInner {
-        unsafe { mem::transmute(self.0.get()) }
+        unsafe { &mut *self.0.get() }
     }
 }
 
@@ -107,7 +115,6 @@ impl FramerateInner {
             }
         } else if self.samples < Self::SAMPLE_COUNT {
             log::trace!("Framerate: {}", self.cur);
-            return;
         }
     }
 
@@ -122,3 +129,18 @@ impl FramerateInner {
 
 /// The global framerate tracker
 pub static FRAMERATE: Framerate = Framerate::new();
+
+#[cfg(test)]
+mod test {
+    use crate::frame::{DEFAULT_FRAMERATE, Frames};
+
+    #[test]
+    fn test_unlocked_framerate() {
+        assert_eq!(Frames::standard(10).at(DEFAULT_FRAMERATE), 10);
+        assert_eq!(Frames::standard(10).at(144), 24);
+        // the longest settings never overflow
+        assert_eq!(Frames::standard(600).at(144), 1440);
+        assert_eq!(Frames::standard(600).at(360), 3600);
+        assert_eq!(Frames::standard(u16::MAX).at(144), u16::MAX);
+    }
+}
@@ -1,101 +1,277 @@
-use std::fmt::Debug;
+use std::{
+    fmt::{Debug, Display},
+    mem,
+    str::FromStr,
+    time::SystemTime,
+};
 
 use Input::*;
 
-use crate::frame::Frames;
-
-// buffer behavior
-const MAX_INTERVAL: Frames = Frames::standard(10);
-const MAX_DELAY: Frames = Frames::standard(10);
-const MAX_DELAY_FOR_SINGLE_INPUT: Frames = Frames::standard(2);
-// joystick ergonomics
-const COMMON_THRESHOLD: f32 = 0.85;
-const ROTATE_THRESHOLD: f32 = 0.9;
-const BOUNCE_THRESHOLD: f32 = 0.4;
+use crate::{
+    config::{Calibration, DeadzoneShape, Settings, Socd},
+    frame::Frames,
+};
 
 //----------------------------------------------------------------------------
 //
-//  An input buffer that remembers the most recent 3 motion inputs
+//  An input buffer that remembers the most recent motion inputs, as many as the longest configured sequence
 //  The buffer expires after several frames unless new inputs are pushed into it and reset its age
 //
 //----------------------------------------------------------------------------
 pub struct InputBuffer {
+    settings: Settings,
+    capacity: u8,
     inputs: Inputs,
     age: u16,
-    neutral: bool,
+    // whether each stick is in its neutral zone, and the stick of the last reading
+    neutral: [bool; 2],
+    stick: Option<Stick>,
     keys_down: [bool; 4],
+    // the order each held key is pressed in, shared by the keys pressed together
+    press_orders: [u64; 4],
+    presses: u64,
+    // states for chording keys into diagonals
+    // on each axis the opposite keys held together are resolved by the SOCD policy
+    vertical: Option<Input>,
+    horizontal: Option<Input>,
+    key_direction: Option<Input>,
+    key_direction_age: u16,
+    pending: Option<Input>,
 }
 
 impl InputBuffer {
-    pub const fn new() -> InputBuffer {
+    pub const fn new(settings: Settings, capacity: u8) -> InputBuffer {
         InputBuffer {
+            settings,
+            capacity,
             inputs: Inputs::new(),
             age: 0,
-            neutral: true,
+            neutral: [true; 2],
+            stick: None,
             keys_down: [false; 4],
+            press_orders: [0; 4],
+            presses: 0,
+            vertical: None,
+            horizontal: None,
+            key_direction: None,
+            key_direction_age: 0,
+            pending: None,
         }
     }
 
+    pub fn configure(&mut self, settings: Settings, capacity: u8) {
+        self.settings = settings;
+        self.capacity = capacity;
+    }
+
     pub fn update_keys(&mut self, up: bool, right: bool, down: bool, left: bool) -> Inputs {
+        self.update(Reading {
+            keys: [up, right, down, left],
+            ..Reading::default()
+        })
+    }
+
+    pub fn update_stick(&mut self, stick: Stick, x: f32, y: f32) -> Inputs {
+        self.update(Reading {
+            stick: Some((stick, x, y)),
+            ..Reading::default()
+        })
+    }
+
+    /// Feeds the keys and the stick of the same frame together, which ages the buffer only once
+    pub fn update(&mut self, reading: Reading) -> Inputs {
+        // the stick that's no longer read can't be told apart from the neutral zone. both start over from there
+        let stick = reading.stick.map(|(stick, _, _)| stick);
+        if stick != self.stick {
+            self.neutral = [true; 2];
+            self.stick = stick;
+        }
+        let mut updated = match reading.stick {
+            Some((stick, x, y)) => self.feed_stick(stick, x, y),
+            None => false,
+        };
+        updated |= self.feed_keys(reading.keys, reading.pressed_at);
+        self.age(updated);
+        self.inputs
+    }
+
+    fn feed_keys(&mut self, keys: [bool; 4], pressed_at: [Option<SystemTime>; 4]) -> bool {
+        let socd = self.settings.socd;
+        let last_axes = [self.resolve_axis(0), self.resolve_axis(1)];
+        // newly pressed keys in the order they're pressed. keys without timestamps are pressed together
+        let mut order = [0, 1, 2, 3];
+        order.sort_by_key(|i| pressed_at[*i]);
+        let mut updated = false;
+        let mut last_pressed_at = None;
+        for i in order {
+            if !self.keys_down[i] && keys[i] {
+                if last_pressed_at != Some(pressed_at[i]) {
+                    self.presses += 1;
+                    last_pressed_at = Some(pressed_at[i]);
+                }
+                self.press_orders[i] = self.presses;
+                if socd == Socd::Both && !self.settings.diagonals {
+                    self.push(Input::from_repr(i as u8 * 2));
+                    updated = true;
+                }
+            }
+        }
+        self.keys_down = keys;
+        if self.settings.diagonals {
+            return self.feed_chorded_keys();
+        }
+        if socd == Socd::Both {
+            return updated;
+        }
+
+        // each axis makes an input when it turns to another direction
+        let mut changes = [0, 1].map(|axis| match self.resolve_axis(axis) {
+            Some(input) if Some(input) != last_axes[axis] => Some((self.press_orders[input as usize / 2], input)),
+            _ => None,
+        });
+        changes.sort_by_key(|it| it.map(|(order, _)| order));
+        for (_, input) in changes.into_iter().flatten() {
+            self.push(input);
+            updated = true;
+        }
+        updated
+    }
+
+    // the direction of the vertical (0) or the horizontal (1) axis, where opposite keys are resolved by the SOCD policy
+    fn resolve_axis(&self, axis: usize) -> Option<Input> {
+        let (i, j) = (axis, axis + 2);
+        let input = |i: usize| Some(Input::from_repr(i as u8 * 2));
+        match (self.keys_down[i], self.keys_down[j]) {
+            (false, false) => None,
+            (true, false) => input(i),
+            (false, true) => input(j),
+            (true, true) => {
+                let (a, b) = (self.press_orders[i], self.press_orders[j]);
+                match self.settings.socd {
+                    Socd::Neutral => None,
+                    // the keys are pressed together so that neither of them is the last. `both` keeps the old order
+                    Socd::Both if a == b => input(j),
+                    Socd::LastInputWins | Socd::FirstInputWins if a == b => None,
+                    Socd::Both | Socd::LastInputWins => input(if a > b { i } else { j }),
+                    Socd::FirstInputWins => input(if a < b { i } else { j }),
+                }
+            }
+        }
+    }
+
+    // keys held together make diagonals, e.g., W + D makes ↗
+    fn feed_chorded_keys(&mut self) -> bool {
+        self.vertical = self.resolve_axis(0);
+        self.horizontal = self.resolve_axis(1);
+
         let mut updated = false;
-        for (i, (down, input)) in [(up, Up), (right, Right), (down, Down), (left, Left)]
-            .into_iter()
-            .enumerate()
-        {
-            if !self.keys_down[i] && down {
-                // newly pressed key
+        let direction = Input::combine(self.vertical, self.horizontal);
+        let chord_window = self.settings.chord_window.as_actual();
+        if direction != self.key_direction {
+            self.pending = None;
+            match (self.key_direction, direction) {
+                (_, None) => (),
+                // the 2nd key of a chord comes shortly after the 1st one, which turns the last input into a diagonal
+                (Some(last), Some(input))
+                    if input.is_diagonal()
+                        && input.is_adjacent_to(last)
+                        && self.key_direction_age < chord_window
+                        && self.inputs.last() == Some(last) =>
+                {
+                    self.inputs.pop();
+                    self.inputs.push(input);
+                    updated = true;
+                }
+                // releasing a key of a chord only counts when the other key is held long enough,
+                // since the 2 keys are rarely released at the same time
+                (Some(last), Some(input)) if last.is_diagonal() && input.is_adjacent_to(last) && chord_window > 0 => {
+                    self.pending = Some(input);
+                }
+                (_, Some(input)) => {
+                    self.push(input);
+                    updated = true;
+                }
+            }
+            self.key_direction = direction;
+            self.key_direction_age = 0;
+        } else {
+            self.key_direction_age = self.key_direction_age.saturating_add(1);
+            if self.key_direction_age >= chord_window
+                && let Some(input) = self.pending.take()
+            {
                 self.push(input);
                 updated = true;
             }
-            self.keys_down[i] = down;
         }
-        self.age(updated);
-        self.inputs
+        updated
     }
 
-    pub fn update_joystick(&mut self, x: f32, y: f32) -> Inputs {
+    fn feed_stick(&mut self, stick: Stick, x: f32, y: f32) -> bool {
         let mut updated = false;
         let x_abs = x.abs();
         let y_abs = y.abs();
 
-        let input = if y_abs >= x_abs {
+        let input = if self.settings.diagonals {
+            // the angle goes clockwise from ↑, ranging from 0 to 360 degrees
+            let angle = x.atan2(y).to_degrees().rem_euclid(360.0);
+            let diagonal = (angle / 90.0).floor() * 90.0 + 45.0;
+            if (angle - diagonal).abs() <= self.settings.diagonal_sector / 2.0 {
+                Input::from_repr((diagonal / 45.0) as u8 % 8)
+            } else {
+                Input::from_repr((angle / 90.0).round() as u8 % 4 * 2)
+            }
+        } else if y_abs >= x_abs {
             if y > 0.0 { Up } else { Down }
         } else {
             if x > 0.0 { Right } else { Left }
         };
 
         // using chebyshev distance means we have a square-shaped neutral zone
+        // radial deadzones of the calibration stretch the positions onto the square beforehand
         let distance = f32::max(x_abs, y_abs);
+        let settings = &self.settings;
+        // each stick has its own thresholds
+        let (common_threshold, rotate_threshold, bounce_threshold) = match stick {
+            Stick::Left => (
+                settings.common_threshold,
+                settings.rotate_threshold,
+                settings.bounce_threshold,
+            ),
+            Stick::Right => (
+                settings.right_common_threshold,
+                settings.right_rotate_threshold,
+                settings.right_bounce_threshold,
+            ),
+        };
         let threshold = if let Some(last) = self.inputs.last() {
             if input == last {
-                COMMON_THRESHOLD
+                common_threshold
             } else if input == last.opposite() {
                 // makes bouncing inputs (↑↓, ↓↑, ←→, →←) easier by using a smaller threshold
-                BOUNCE_THRESHOLD
+                bounce_threshold
             } else {
-                // makes rotating inputs (↑→, →↓, ↓←, ←↑) HARDER by using a bigger threshold
-                ROTATE_THRESHOLD
+                // makes rotating inputs (↑→, →↓, ↓←, ←↑, ↑↗...) HARDER by using a bigger threshold
+                rotate_threshold
             }
         } else {
-            COMMON_THRESHOLD
+            common_threshold
         };
 
+        let neutral = &mut self.neutral[stick as usize];
         if distance < threshold {
-            self.neutral = true;
+            *neutral = true;
         } else {
-            if self.neutral || self.inputs.last().into_iter().any(|last| input != last) {
+            let was_neutral = mem::replace(neutral, false);
+            if was_neutral || self.inputs.last().into_iter().any(|last| input != last) {
                 self.push(input);
                 updated = true;
             }
-            self.neutral = false;
         }
-
-        self.age(updated);
-        self.inputs.clone()
+        updated
     }
 
     fn push(&mut self, input: Input) {
-        if self.inputs.len() >= Inputs::CAP || self.age > MAX_INTERVAL.as_actual() {
+        if self.inputs.len() >= self.capacity || self.age > self.settings.max_interval.as_actual() {
             self.inputs.clear();
         }
         self.inputs.push(input);
@@ -111,14 +287,14 @@ impl InputBuffer {
 
     pub fn expired(&self) -> bool {
         if self.inputs.len() == 1 {
-            self.age >= MAX_DELAY_FOR_SINGLE_INPUT.as_actual() && self.released()
+            self.age >= self.settings.max_delay_for_single_input.as_actual() && self.released()
         } else {
-            self.age >= MAX_DELAY.as_actual()
+            self.age >= self.settings.max_delay.as_actual()
         }
     }
 
     fn released(&self) -> bool {
-        self.neutral && self.keys_down == [false, false, false, false]
+        self.neutral == [true; 2] && self.keys_down == [false, false, false, false]
     }
 
     pub fn clear(&mut self) {
@@ -127,6 +303,132 @@ impl InputBuffer {
     }
 }
 
+/// What the motion sources read in a frame
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Reading {
+    /// Whether ↑→↓← are held, on the keys or the D-pad
+    pub keys: [bool; 4],
+    /// When each of the keys is pressed, which orders the keys pressed in the same frame. `None` when unknown
+    pub pressed_at: [Option<SystemTime>; 4],
+    /// The stick off the center and its position. `None` when all the sticks are in the center
+    pub stick: Option<(Stick, f32, f32)>,
+}
+
+/// The sticks that make motion inputs. The right one is usually for the camera
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Stick {
+    Left,
+    Right,
+}
+
+//----------------------------------------------------------------------------
+//
+//  Stick calibration, which turns the raw positions of a stick into the ones fed to the input buffer
+//  The drift of a worn stick is learned from where it rests
+//
+//----------------------------------------------------------------------------
+
+// how far a resting stick may wander and how far from the center its drift can be
+const REST_JITTER: f32 = 0.02;
+const MAX_DRIFT: f32 = 0.2;
+// how far from the center a resting stick is taken as drifting when there's no inner deadzone.
+// a tilt beyond it or the deadzone is held by the player however long it's held
+const DRIFT_WINDOW: f32 = 0.1;
+// how long a stick rests before its position is learned
+const REST_DURATION: Frames = Frames::standard(60);
+
+pub struct StickCalibrator {
+    calibration: Calibration,
+    offset: (f32, f32),
+    // where the stick started resting and for how long
+    rest: (f32, f32),
+    rest_age: u16,
+}
+
+impl StickCalibrator {
+    pub fn new(calibration: Calibration) -> StickCalibrator {
+        StickCalibrator {
+            calibration,
+            offset: calibration.offset,
+            rest: (0.0, 0.0),
+            rest_age: 0,
+        }
+    }
+
+    /// Where the stick rests, either specified or learned
+    pub fn offset(&self) -> (f32, f32) {
+        self.offset
+    }
+
+    /// Must be called once per frame so that the drift is learned in time
+    pub fn calibrate(&mut self, x: f32, y: f32) -> (f32, f32) {
+        if self.calibration.drift_compensation {
+            self.learn(x, y);
+        }
+        let calibration = &self.calibration;
+        // the range on each side of the offset is stretched back to 1
+        let recenter = |value: f32, offset: f32| {
+            if value >= offset {
+                (value - offset) / (1.0 - offset)
+            } else {
+                (value - offset) / (1.0 + offset)
+            }
+        };
+        let mut pos = [recenter(x, self.offset.0), recenter(y, self.offset.1)];
+        for (i, value) in pos.iter_mut().enumerate() {
+            if calibration.invert[i] {
+                *value = -*value;
+            }
+            *value *= calibration.scale[i];
+        }
+        let [x, y] = pos;
+
+        let inner = calibration.inner_deadzone;
+        let outer = calibration.outer_deadzone;
+        let chebyshev = f32::max(x.abs(), y.abs());
+        match calibration.deadzone {
+            DeadzoneShape::Square => {
+                if chebyshev < inner {
+                    return (0.0, 0.0);
+                }
+                ((x / outer).clamp(-1.0, 1.0), (y / outer).clamp(-1.0, 1.0))
+            }
+            shape => {
+                let distance = x.hypot(y);
+                if distance == 0.0 || distance < inner {
+                    return (0.0, 0.0);
+                }
+                let distance = if shape == DeadzoneShape::ScaledRadial {
+                    (distance.min(outer) - inner) / (outer - inner)
+                } else {
+                    (distance / outer).min(1.0)
+                };
+                // stretched onto the square so that the thresholds of the input buffer measure the radial distance
+                let stretch = distance / chebyshev;
+                (x * stretch, y * stretch)
+            }
+        }
+    }
+
+    fn learn(&mut self, x: f32, y: f32) {
+        if f32::max((x - self.rest.0).abs(), (y - self.rest.1).abs()) < REST_JITTER {
+            self.rest_age = self.rest_age.saturating_add(1);
+        } else {
+            self.rest = (x, y);
+            self.rest_age = 0;
+        }
+        // a stick held still off the center is held by the player
+        let distance = f32::max(self.rest.0.abs(), self.rest.1.abs());
+        let window = match self.calibration.inner_deadzone {
+            0.0 => DRIFT_WINDOW,
+            inner => inner.min(MAX_DRIFT),
+        };
+        if self.rest_age >= REST_DURATION.as_actual() && distance < window {
+            self.offset = self.rest;
+        }
+    }
+}
+
 //----------------------------------------------------------------------------
 //
 //  The input enum.
@@ -134,30 +436,84 @@ impl InputBuffer {
 //----------------------------------------------------------------------------
 #[derive(Clone, Copy, Hash, PartialEq, Eq)]
 pub enum Input {
+    // the directions go clockwise so that rotating is simply adding to the representation
     Up = 0,
-    Right = 1,
-    Down = 2,
-    Left = 3,
+    UpRight = 1,
+    Right = 2,
+    DownRight = 3,
+    Down = 4,
+    DownLeft = 5,
+    Left = 6,
+    UpLeft = 7,
 }
 
 impl Input {
+    pub const COUNT: usize = 8;
+
     #[inline(always)]
     pub fn opposite(self) -> Input {
-        Input::from_repr((self.as_repr() + 2) % 4)
+        Input::from_repr((self.as_repr() + 4) % 8)
     }
 
+    /// Rotates the direction by 90 degrees clockwise
     #[inline(always)]
     pub fn rotate(self) -> Input {
-        Input::from_repr((self.as_repr() + 1) % 4)
+        Input::from_repr((self.as_repr() + 2) % 8)
+    }
+
+    #[inline(always)]
+    pub fn is_diagonal(self) -> bool {
+        self.as_repr() % 2 == 1
+    }
+
+    /// Whether the two directions are 45 degrees apart, e.g., ↑ and ↗
+    #[inline(always)]
+    pub fn is_adjacent_to(self, other: Input) -> bool {
+        matches!((self.as_repr() + 8 - other.as_repr()) % 8, 1 | 7)
+    }
+
+    /// The diagonal between two cardinal directions 90 degrees apart, e.g., ↗ between ↑ and →
+    #[inline(always)]
+    pub fn between(self, other: Input) -> Option<Input> {
+        let repr = self.as_repr();
+        match (other.as_repr() + 8 - repr) % 8 {
+            _ if self.is_diagonal() => None,
+            2 => Some(Input::from_repr(repr + 1)),
+            6 => Some(Input::from_repr((repr + 7) % 8)),
+            _ => None,
+        }
+    }
+
+    /// Combines the directions of the vertical and the horizontal axes
+    #[inline(always)]
+    pub fn combine(vertical: Option<Input>, horizontal: Option<Input>) -> Option<Input> {
+        match (vertical, horizontal) {
+            (Some(Up), Some(Right)) => Some(UpRight),
+            (Some(Down), Some(Right)) => Some(DownRight),
+            (Some(Down), Some(Left)) => Some(DownLeft),
+            (Some(Up), Some(Left)) => Some(UpLeft),
+            (Some(vertical), _) => Some(vertical),
+            (None, horizontal) => horizontal,
+        }
+    }
+
+    /// The digit of the direction on the numpad
+    #[inline(always)]
+    pub fn numpad(self) -> char {
+        ['8', '9', '6', '3', '2', '1', '4', '7'][self.as_repr() as usize]
     }
 
     #[inline(always)]
     fn from_repr(repr: u8) -> Input {
         match repr {
             0 => Up,
-            1 => Right,
-            2 => Down,
-            3 => Left,
+            1 => UpRight,
+            2 => Right,
+            3 => DownRight,
+            4 => Down,
+            5 => DownLeft,
+            6 => Left,
+            7 => UpLeft,
             _ => panic!("Illegal representation {repr}."),
         }
     }
@@ -171,11 +527,16 @@ impl Input {
 impl TryFrom<char> for Input {
     type Error = ();
     fn try_from(value: char) -> Result<Self, Self::Error> {
+        // directions can also be spelled out with the digits on the numpad
         match value.to_ascii_uppercase() {
-            '↑' | 'U' => Ok(Up),
-            '→' | 'R' => Ok(Right),
-            '↓' | 'D' => Ok(Down),
-            '←' | 'L' => Ok(Left),
+            '↑' | 'U' | '8' => Ok(Up),
+            '↗' | '9' => Ok(UpRight),
+            '→' | 'R' | '6' => Ok(Right),
+            '↘' | '3' => Ok(DownRight),
+            '↓' | 'D' | '2' => Ok(Down),
+            '↙' | '1' => Ok(DownLeft),
+            '←' | 'L' | '4' => Ok(Left),
+            '↖' | '7' => Ok(UpLeft),
             _ => Err(()),
         }
     }
@@ -185,9 +546,13 @@ impl Debug for Input {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             Self::Up => write!(f, "↑"),
+            Self::UpRight => write!(f, "↗"),
             Self::Right => write!(f, "→"),
+            Self::DownRight => write!(f, "↘"),
             Self::Down => write!(f, "↓"),
+            Self::DownLeft => write!(f, "↙"),
             Self::Left => write!(f, "←"),
+            Self::UpLeft => write!(f, "↖"),
         }
     }
 }
@@ -199,30 +564,31 @@ impl Debug for Input {
 //
 //----------------------------------------------------------------------------
 
-#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+#[derive(Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub struct Inputs {
     // the bit-wise content of `value` follows the pattern
-    // [inputs[0], inputs[1], inputs[2], len]
-    // the possible values for inputs[n] and len are both 0, 1, 2, 3
-    // thus each of the values takes exactly 2 bits of space
-    // making 4 of them to fit into an 8-bit integer
-    value: u8,
+    // [inputs[0], inputs[1], ..., inputs[CAP - 1], len]
+    // each of the inputs takes `INPUT_BITS` bits and the length takes `LEN_BITS` bits
+    // the unused inputs are always zeroed so that the value is a perfect hash of the sequence
+    value: u32,
 }
 
 impl Inputs {
-    const CAP: u8 = 3;
-    const MAX_HASHCODE: usize = 0b11111111;
+    pub const CAP: u8 = 8;
+    const INPUT_BITS: u8 = 3;
+    const INPUT_MASK: u32 = (1 << Inputs::INPUT_BITS) - 1;
+    const LEN_BITS: u8 = 4;
+    const LEN_MASK: u32 = (1 << Inputs::LEN_BITS) - 1;
 
     #[inline(always)]
     pub const fn new() -> Inputs {
         Inputs { value: 0 }
     }
 
+    // where the i-th input is stored in `value`
     #[inline(always)]
-    pub fn from_perfect_hash(perfect_hash: usize) -> Inputs {
-        Inputs {
-            value: perfect_hash as u8,
-        }
+    const fn shift(i: u8) -> u8 {
+        Inputs::LEN_BITS + (Inputs::CAP - 1 - i) * Inputs::INPUT_BITS
     }
 
     #[inline(always)]
@@ -231,7 +597,7 @@ impl Inputs {
         if len == Inputs::CAP {
             false
         } else {
-            self.value += input.as_repr() << ((Inputs::CAP - len) * 2);
+            self.value += (input.as_repr() as u32) << Inputs::shift(len);
             self.value += 1;
             true
         }
@@ -243,22 +609,28 @@ impl Inputs {
         if len == 0 {
             None
         } else {
-            let shift = (Inputs::CAP + 1 - len) * 2;
-            let last = self.value >> shift & 0b11;
-            self.value &= !(0b11 << shift);
+            let shift = Inputs::shift(len - 1);
+            let last = self.value >> shift & Inputs::INPUT_MASK;
+            self.value &= !(Inputs::INPUT_MASK << shift);
             self.value -= 1;
-            Some(Input::from_repr(last))
+            Some(Input::from_repr(last as u8))
         }
     }
 
     #[inline(always)]
     pub fn last(self) -> Option<Input> {
         let len = self.len();
-        if len == 0 {
-            None
-        } else {
-            Some(Input::from_repr(self.value >> ((Inputs::CAP + 1 - len) * 2) & 0b11))
-        }
+        if len == 0 { None } else { Some(self.nth(len - 1)) }
+    }
+
+    #[inline(always)]
+    fn nth(self, i: u8) -> Input {
+        Input::from_repr((self.value >> Inputs::shift(i) & Inputs::INPUT_MASK) as u8)
+    }
+
+    #[inline(always)]
+    pub fn iter(self) -> impl Iterator<Item = Input> {
+        (0..self.len()).map(move |i| self.nth(i))
     }
 
     #[inline(always)]
@@ -277,7 +649,12 @@ impl Inputs {
 
     #[inline(always)]
     pub fn len(self) -> u8 {
-        self.value & 0b11
+        (self.value & Inputs::LEN_MASK) as u8
+    }
+
+    #[inline(always)]
+    pub fn is_empty(self) -> bool {
+        self.len() == 0
     }
 
     #[inline(always)]
@@ -289,14 +666,32 @@ impl Inputs {
     pub fn meant_for_art(self) -> bool {
         self.len() >= 2
     }
+
+    /// The other ways the stick makes the sequence when diagonals are on. It passes through the diagonal
+    /// between two directions 90 degrees apart, or skips it when it turns fast, e.g., `←↖↑` or `←↑` for `←↑`
+    pub fn sweeps(self) -> Vec<Inputs> {
+        let mut sweeps = vec![Inputs::new()];
+        let mut prev = None::<Input>;
+        for input in self.iter() {
+            if let Some(diagonal) = prev.and_then(|it| it.between(input)) {
+                let mut through = sweeps.clone();
+                through.retain_mut(|it| it.push(diagonal));
+                sweeps.extend(through);
+            }
+            // sequences too long for the buffer are never made
+            sweeps.retain_mut(|it| it.push(input));
+            prev = Some(input);
+        }
+        sweeps.retain(|it| *it != self);
+        sweeps
+    }
 }
 
 impl FromIterator<Input> for Inputs {
     #[inline(always)]
     fn from_iter<T: IntoIterator<Item = Input>>(iter: T) -> Self {
         let mut inputs = Inputs::new();
-        let mut iter = iter.into_iter();
-        while let Some(input) = iter.next() {
+        for input in iter {
             if !inputs.push(input) {
                 panic!("Number of inputs exceeds capacity.")
             }
@@ -315,73 +710,225 @@ impl From<&[Input]> for Inputs {
 impl<const N: usize> From<[Input; N]> for Inputs {
     #[inline(always)]
     fn from(array: [Input; N]) -> Self {
-        Inputs::from_iter(array.into_iter())
+        Inputs::from_iter(array)
     }
 }
 
 impl Debug for Inputs {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let mut inputs = [Input::Up; Self::CAP as usize];
-        let mut len = 0;
+        f.debug_list().entries(self.iter()).finish()
+    }
+}
+
+impl Display for Inputs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.display(Notation::Arrows), f)
+    }
+}
 
-        let mut cloned = self.clone();
-        while let Some(input) = cloned.pop() {
-            inputs[len] = input;
-            len += 1;
+impl FromStr for Inputs {
+    type Err = ParseInputsError;
+
+    /// Accepts arrows (`↓↘→`), letters (`DR`), numpad digits (`236`) or a mix of them.
+    /// `∅`, `NONE` and `5` stand for the empty sequence
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.is_empty() {
+            return Err(ParseInputsError::Empty);
+        }
+        if matches!(s.to_ascii_uppercase().as_str(), "∅" | "NONE" | "5") {
+            return Ok(Inputs::new());
+        }
+        let mut inputs = Vec::new();
+        for (offset, ch) in s.chars().enumerate() {
+            let input = Input::try_from(ch).map_err(|_| ParseInputsError::UnknownToken { offset, token: ch })?;
+            inputs.push(input);
+        }
+        if inputs.len() > Inputs::CAP as usize {
+            return Err(ParseInputsError::TooLong { len: inputs.len() });
+        }
+        Ok(inputs.into_iter().collect())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseInputsError {
+    Empty,
+    /// `offset` is counted in characters
+    UnknownToken {
+        offset: usize,
+        token: char,
+    },
+    TooLong {
+        len: usize,
+    },
+}
+
+impl Display for ParseInputsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ParseInputsError::Empty => f.write_str("No input is specified."),
+            ParseInputsError::UnknownToken { token, .. } => write!(f, "Unknown token `{token}`."),
+            ParseInputsError::TooLong { len } => {
+                write!(f, "{len} inputs are given while at most {} are supported.", Inputs::CAP)
+            }
         }
-        let inputs = &mut inputs[..len];
-        inputs.reverse();
-        f.debug_list().entries(inputs).finish()
     }
 }
 
 //----------------------------------------------------------------------------
 //
-//  An array-based trie that uses input sequence as keys
+//  Notations to write input sequences in
+//
+//----------------------------------------------------------------------------
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Notation {
+    /// `↓↘→`, with `∅` for the empty sequence
+    #[default]
+    Arrows,
+    /// `236` as in fighting games, with `5` for the empty sequence
+    Numpad,
+}
+
+impl Notation {
+    /// Tells which notation a sequence is written in
+    pub fn of(s: &str) -> Notation {
+        if s.chars().any(|ch| ch.is_ascii_digit()) {
+            Notation::Numpad
+        } else {
+            Notation::Arrows
+        }
+    }
+}
+
+impl Inputs {
+    pub fn display(self, notation: Notation) -> impl Display {
+        DisplayInputs { inputs: self, notation }
+    }
+}
+
+struct DisplayInputs {
+    inputs: Inputs,
+    notation: Notation,
+}
+
+impl Display for DisplayInputs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // writes into a string first so that paddings work
+        let s = match self.notation {
+            Notation::Arrows if self.inputs.is_empty() => "∅".to_string(),
+            Notation::Arrows => self.inputs.iter().map(|input| format!("{input:?}")).collect(),
+            Notation::Numpad if self.inputs.is_empty() => "5".to_string(),
+            Notation::Numpad => self.inputs.iter().map(Input::numpad).collect(),
+        };
+        f.pad(&s)
+    }
+}
+
+//----------------------------------------------------------------------------
+//
+//  A trie that uses input sequence as keys
+//  Nodes are stored in a vec and refer to their children by indices
 //
 //----------------------------------------------------------------------------
 pub struct InputsTrie<T> {
-    array: [Option<T>; Inputs::MAX_HASHCODE + 1],
+    // the first node is always the root, which is never a child of any node
+    // thus index 0 can be used to indicate the absence of children
+    nodes: Vec<Node<T>>,
 }
 
-impl<T: Copy> InputsTrie<T> {
+struct Node<T> {
+    children: [u32; Input::COUNT],
+    value: Option<T>,
+}
+
+impl<T> Node<T> {
+    fn new() -> Node<T> {
+        Node {
+            children: [0; Input::COUNT],
+            value: None,
+        }
+    }
+}
+
+impl<T> InputsTrie<T> {
     pub fn new() -> InputsTrie<T> {
         InputsTrie {
-            array: [None; Inputs::MAX_HASHCODE + 1],
+            nodes: vec![Node::new()],
         }
     }
 
-    pub fn get(&self, inputs: impl Into<Inputs>) -> Option<T> {
-        self.array[inputs.into().perfect_hash()]
+    pub fn get(&self, inputs: impl Into<Inputs>) -> Option<&T> {
+        let mut node = &self.nodes[0];
+        for input in inputs.into().iter() {
+            let child = node.children[input.as_repr() as usize];
+            if child == 0 {
+                return None;
+            }
+            node = &self.nodes[child as usize];
+        }
+        node.value.as_ref()
     }
 
     pub fn insert(&mut self, inputs: impl Into<Inputs>, value: T) {
-        self.array[inputs.into().perfect_hash()] = Some(value);
+        self.entry(inputs.into()).replace(value);
     }
 
     pub fn try_insert(&mut self, inputs: impl Into<Inputs>, value: T) {
-        self.array[inputs.into().perfect_hash()].get_or_insert(value);
+        self.entry(inputs.into()).get_or_insert(value);
+    }
+
+    fn entry(&mut self, inputs: Inputs) -> &mut Option<T> {
+        let mut index = 0;
+        for input in inputs.iter() {
+            let child = self.nodes[index].children[input.as_repr() as usize];
+            index = if child == 0 {
+                self.nodes.push(Node::new());
+                let child = self.nodes.len() - 1;
+                self.nodes[index].children[input.as_repr() as usize] = child as u32;
+                child
+            } else {
+                child as usize
+            };
+        }
+        &mut self.nodes[index].value
+    }
+
+    /// Iterates the entries in the lexicographical order of the sequences
+    pub fn iter(&self) -> impl Iterator<Item = (Inputs, &T)> {
+        let mut stack = vec![(0, Inputs::new())];
+        std::iter::from_fn(move || {
+            while let Some((index, inputs)) = stack.pop() {
+                let node = &self.nodes[index];
+                for (repr, child) in node.children.iter().copied().enumerate().rev() {
+                    if child != 0 {
+                        let mut inputs = inputs;
+                        inputs.push(Input::from_repr(repr as u8));
+                        stack.push((child as usize, inputs));
+                    }
+                }
+                if let Some(value) = node.value.as_ref() {
+                    return Some((inputs, value));
+                }
+            }
+            None
+        })
     }
+}
 
-    pub fn iter(&self) -> impl Iterator<Item = (Inputs, T)> {
-        self.array
-            .iter()
-            .copied()
-            .enumerate()
-            .filter_map(|(hash, value)| Some((Inputs::from_perfect_hash(hash), value?)))
+impl<T> InputsTrie<Vec<T>> {
+    pub fn get_or_default(&self, inputs: impl Into<Inputs>) -> &[T] {
+        self.get(inputs).map(Vec::as_slice).unwrap_or_default()
     }
 }
 
-impl<T: Default + Copy> InputsTrie<T> {
-    pub fn get_or_default(&self, inputs: impl Into<Inputs>) -> T {
-        self.array[inputs.into().perfect_hash()].unwrap_or_default()
+impl<T> Default for InputsTrie<T> {
+    fn default() -> Self {
+        InputsTrie::new()
     }
 }
 
-impl<T> Debug for InputsTrie<T>
-where
-    T: Debug + Copy,
-{
+impl<T: Debug> Debug for InputsTrie<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_map().entries(self.iter()).finish()
     }
@@ -389,7 +936,16 @@ where
 
 #[cfg(test)]
 mod test {
-    use crate::input::{Input::*, Inputs};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use crate::{
+        config::{Calibration, DeadzoneShape, Settings, Socd},
+        input::{
+            Input, Input::*, InputBuffer, Inputs, InputsTrie, Notation, ParseInputsError, Reading, Stick,
+            StickCalibrator,
+        },
+    };
+
     #[test]
     fn test_inputs() {
         macro_rules! assert_len {
@@ -398,32 +954,12 @@ mod test {
             };
         }
 
-        macro_rules! assert_value {
-            ($inputs:expr, $value:expr) => {
-                assert_eq!(
-                    Inputs::from($inputs).value,
-                    u8::from_str_radix($value, 4).unwrap()
-                );
-            };
-        }
-
         // len
         assert_len!([], 0);
         assert_len!([Up], 1);
         assert_len!([Up, Right], 2);
         assert_len!([Up, Right, Down], 3);
 
-        // hash
-        assert_value!([], "0000");
-        assert_value!([Up], "0001");
-        assert_value!([Right], "1001");
-        assert_value!([Down], "2001");
-        assert_value!([Left], "3001");
-        assert_value!([Up, Up], "0002");
-        assert_value!([Up, Right], "0102");
-        assert_value!([Up, Right, Down], "0123");
-        assert_value!([Left, Left, Left], "3333");
-
         // push and pop
         let src = [Up, Right, Down];
         let rev = [Down, Right, Up];
@@ -433,7 +969,6 @@ mod test {
             assert!(inputs.push(input));
             assert_eq!(inputs, Inputs::from(&src[..i + 1]));
         }
-        assert_eq!(inputs.push(Left), false);
 
         for last in rev {
             assert_eq!(inputs.last(), Some(last));
@@ -448,6 +983,487 @@ mod test {
         assert_eq!(Inputs::from([Up, Right, Down]).rev(), Inputs::from([Down, Right, Up]));
     }
 
+    #[test]
+    fn test_long_inputs() {
+        macro_rules! assert_value {
+            ($inputs:expr, $value:expr) => {
+                // inputs in octal followed by the length in decimal
+                let (inputs, len) = $value.split_once('_').unwrap();
+                assert_eq!(
+                    Inputs::from($inputs).value,
+                    u32::from_str_radix(inputs, 8).unwrap() << Inputs::LEN_BITS | len.parse::<u32>().unwrap()
+                );
+            };
+        }
+
+        // hash
+        assert_value!([], "00000000_0");
+        assert_value!([Up], "00000000_1");
+        assert_value!([Right], "20000000_1");
+        assert_value!([Down], "40000000_1");
+        assert_value!([Left], "60000000_1");
+        assert_value!([UpLeft], "70000000_1");
+        assert_value!([Up, Up], "00000000_2");
+        assert_value!([Up, Right], "02000000_2");
+        assert_value!([Up, Right, Down], "02400000_3");
+        assert_value!([Down, DownRight, Right], "43200000_3");
+        assert_value!([Left, Left, Left], "66600000_3");
+        assert_value!([Down, Right, Right, Down, Right, Right], "42242200_6");
+        assert_value!([Left, Left, Left, Left, Left, Left, Left, Left], "66666666_8");
+        assert_value!(
+            [UpLeft, UpLeft, UpLeft, UpLeft, UpLeft, UpLeft, UpLeft, UpLeft],
+            "77777777_8"
+        );
+
+        // up to 8 inputs with diagonals
+        let src = [Up, UpRight, Down, Left, DownLeft, Down, Right, Up];
+        let rev = [Up, Right, Down, DownLeft, Left, Down, UpRight, Up];
+
+        let mut inputs = Inputs::new();
+        for (i, input) in src.iter().copied().enumerate() {
+            assert!(inputs.push(input));
+            assert_eq!(inputs, Inputs::from(&src[..i + 1]));
+        }
+        assert_eq!(inputs.len(), 8);
+        assert!(!inputs.push(Left));
+
+        for last in rev {
+            assert_eq!(inputs.last(), Some(last));
+            assert_eq!(inputs.pop(), Some(last));
+        }
+        assert_eq!(inputs.pop(), None);
+
+        // iter
+        assert_eq!(Inputs::from(src).iter().collect::<Vec<_>>(), src);
+    }
+
+    #[test]
+    fn test_trie() {
+        let mut trie = InputsTrie::new();
+        trie.insert([], 0);
+        trie.insert([Up, Up], 1);
+        trie.insert([Down, Right, Right, Down, Right, Right], 2);
+        trie.try_insert([Up, Up], 3);
+        trie.try_insert([Up, Up, Up], 3);
+
+        assert_eq!(trie.get([]), Some(&0));
+        assert_eq!(trie.get([Up]), None);
+        assert_eq!(trie.get([Up, Up]), Some(&1));
+        assert_eq!(trie.get([Up, Up, Up]), Some(&3));
+        assert_eq!(trie.get([Up, Up, Up, Up]), None);
+        assert_eq!(trie.get([Down, Right, Right, Down, Right]), None);
+        assert_eq!(trie.get([Down, Right, Right, Down, Right, Right]), Some(&2));
+        assert_eq!(
+            trie.iter().map(|(inputs, value)| (inputs, *value)).collect::<Vec<_>>(),
+            [
+                (Inputs::from([]), 0),
+                (Inputs::from([Up, Up]), 1),
+                (Inputs::from([Up, Up, Up]), 3),
+                (Inputs::from([Down, Right, Right, Down, Right, Right]), 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_notations() {
+        macro_rules! assert_parse {
+            ($src:expr, $inputs:expr) => {
+                assert_eq!(
+                    $src.parse::<Inputs>(),
+                    Ok(Inputs::from($inputs)),
+                    "{}",
+                    $src
+                );
+            };
+        }
+
+        assert_parse!("236", [Down, DownRight, Right]);
+        assert_parse!("214", [Down, DownLeft, Left]);
+        assert_parse!("623", [Right, Down, DownRight]);
+        assert_parse!("22", [Down, Down]);
+        assert_parse!("↓↘→", [Down, DownRight, Right]);
+        assert_parse!("DR", [Down, Right]);
+        assert_parse!("↓3→", [Down, DownRight, Right]);
+        assert_parse!("5", []);
+        assert_parse!("∅", []);
+        assert_parse!("none", []);
+        assert_eq!("".parse::<Inputs>(), Err(ParseInputsError::Empty));
+        assert_eq!(
+            "2365".parse::<Inputs>(),
+            Err(ParseInputsError::UnknownToken { offset: 3, token: '5' })
+        );
+        assert_eq!("222222222".parse::<Inputs>(), Err(ParseInputsError::TooLong { len: 9 }));
+
+        assert_eq!(Inputs::from([Down, DownRight, Right]).to_string(), "↓↘→");
+        assert_eq!(
+            Inputs::from([Down, DownRight, Right])
+                .display(Notation::Numpad)
+                .to_string(),
+            "236"
+        );
+        assert_eq!(Inputs::new().display(Notation::Numpad).to_string(), "5");
+        assert_eq!(
+            format!("{:<4}|", Inputs::from([Down, Down]).display(Notation::Numpad)),
+            "22  |"
+        );
+        assert_eq!(Notation::of("236"), Notation::Numpad);
+        assert_eq!(Notation::of("↓↘→"), Notation::Arrows);
+
+        // every sequence round-trips in every notation
+        let mut sequences = vec![Inputs::new()];
+        for len in 0..3 {
+            for i in 0..sequences.len() {
+                if sequences[i].len() == len {
+                    for repr in 0..Input::COUNT {
+                        let mut inputs = sequences[i];
+                        inputs.push(Input::from_repr(repr as u8));
+                        sequences.push(inputs);
+                    }
+                }
+            }
+        }
+        for inputs in sequences {
+            for notation in [Notation::Arrows, Notation::Numpad] {
+                let s = inputs.display(notation).to_string();
+                assert_eq!(s.parse::<Inputs>(), Ok(inputs), "{s}");
+                assert_eq!(Notation::of(&s), notation, "{s}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_directions() {
+        assert_eq!(Up.opposite(), Down);
+        assert_eq!(UpRight.opposite(), DownLeft);
+        assert_eq!(Left.rotate(), Up);
+        assert_eq!(DownRight.rotate(), DownLeft);
+        assert!(UpLeft.is_adjacent_to(Up));
+        assert!(UpLeft.is_adjacent_to(Left));
+        assert!(!UpLeft.is_adjacent_to(Right));
+        assert_eq!(Input::combine(Some(Down), Some(Left)), Some(DownLeft));
+        assert_eq!(Input::combine(None, Some(Left)), Some(Left));
+        assert_eq!(Input::combine(None, None), None);
+        assert_eq!(Input::try_from('↗'), Ok(UpRight));
+        assert_eq!(Input::try_from('1'), Ok(DownLeft));
+        assert_eq!(Input::try_from('5'), Err(()));
+    }
+
+    #[test]
+    fn test_socd() {
+        const NONE: [bool; 4] = [false; 4];
+        const LEFT: [bool; 4] = [false, false, false, true];
+        const RIGHT: [bool; 4] = [false, true, false, false];
+        const BOTH: [bool; 4] = [false, true, false, true];
+        let at = |millis| Some(UNIX_EPOCH + Duration::from_millis(millis));
+        let run = |socd, diagonals, frames: &[([bool; 4], [Option<SystemTime>; 4])]| {
+            let settings = Settings {
+                socd,
+                diagonals,
+                ..Settings::default()
+            };
+            let mut buffer = InputBuffer::new(settings, 8);
+            for (keys, pressed_at) in frames.iter().copied() {
+                buffer.update(Reading {
+                    keys,
+                    pressed_at,
+                    stick: None,
+                });
+            }
+            buffer.inputs
+        };
+
+        // holding A and tapping D
+        let tap = [
+            (LEFT, [None; 4]),
+            (BOTH, [None; 4]),
+            (LEFT, [None; 4]),
+            (NONE, [None; 4]),
+        ];
+        assert_eq!(run(Socd::Both, false, &tap), Inputs::from([Left, Right]));
+        assert_eq!(run(Socd::LastInputWins, false, &tap), Inputs::from([Left, Right, Left]));
+        assert_eq!(run(Socd::FirstInputWins, false, &tap), Inputs::from([Left]));
+        assert_eq!(run(Socd::Neutral, false, &tap), Inputs::from([Left, Left]));
+
+        // pressing A and D in the same frame without timestamps
+        let together = [(BOTH, [None; 4]), (NONE, [None; 4])];
+        assert_eq!(run(Socd::Both, false, &together), Inputs::from([Right, Left]));
+        assert_eq!(run(Socd::LastInputWins, false, &together), Inputs::new());
+        assert_eq!(run(Socd::FirstInputWins, false, &together), Inputs::new());
+        assert_eq!(run(Socd::Neutral, false, &together), Inputs::new());
+
+        // the timestamps tell which one comes first. then A is released
+        let ordered = [
+            (BOTH, [None, at(5), None, at(3)]),
+            (RIGHT, [None, at(5), None, None]),
+            (NONE, [None; 4]),
+        ];
+        assert_eq!(run(Socd::Both, false, &ordered), Inputs::from([Left, Right]));
+        assert_eq!(run(Socd::LastInputWins, false, &ordered), Inputs::from([Right]));
+        assert_eq!(run(Socd::FirstInputWins, false, &ordered), Inputs::from([Left, Right]));
+        assert_eq!(run(Socd::Neutral, false, &ordered), Inputs::from([Right]));
+
+        // the policies apply to chorded keys too
+        assert_eq!(run(Socd::Both, true, &tap), Inputs::from([Left, Right, Left]));
+        assert_eq!(run(Socd::LastInputWins, true, &tap), Inputs::from([Left, Right, Left]));
+        assert_eq!(run(Socd::FirstInputWins, true, &tap), Inputs::from([Left]));
+        assert_eq!(run(Socd::Neutral, true, &tap), Inputs::from([Left, Left]));
+        assert_eq!(run(Socd::FirstInputWins, true, &ordered), Inputs::from([Left, Right]));
+    }
+
+    #[test]
+    fn test_diagonals() {
+        let settings = Settings {
+            diagonals: true,
+            ..Settings::default()
+        };
+
+        // stick
+        let mut buffer = InputBuffer::new(settings, 3);
+        for (x, y) in [(0.0, -1.0), (0.95, -0.95), (1.0, 0.0)] {
+            buffer.update_stick(Stick::Left, x, y);
+        }
+        assert_eq!(buffer.inputs, Inputs::from([Down, DownRight, Right]));
+        // a narrow sector for diagonals
+        let mut buffer = InputBuffer::new(
+            Settings {
+                diagonal_sector: 10.0,
+                ..settings
+            },
+            3,
+        );
+        buffer.update_stick(Stick::Left, 0.9, -0.5);
+        assert_eq!(buffer.inputs, Inputs::from([Right]));
+
+        // keys pressed together make a diagonal
+        let mut buffer = InputBuffer::new(settings, 3);
+        buffer.update_keys(false, false, true, false);
+        buffer.update_keys(false, true, true, false);
+        assert_eq!(buffer.inputs, Inputs::from([DownRight]));
+        // and releasing them one by one makes nothing
+        buffer.update_keys(false, true, false, false);
+        buffer.update_keys(false, false, false, false);
+        assert_eq!(buffer.inputs, Inputs::from([DownRight]));
+
+        // keys held one after another make a quarter circle
+        let mut buffer = InputBuffer::new(settings, 3);
+        let frames = [
+            (false, false, true, false),
+            (false, false, true, false),
+            (false, false, true, false),
+            (false, false, true, false),
+            (false, true, true, false),
+            (false, true, true, false),
+            (false, true, true, false),
+            (false, true, true, false),
+            (false, true, false, false),
+            (false, true, false, false),
+            (false, true, false, false),
+            (false, true, false, false),
+        ];
+        for (up, right, down, left) in frames {
+            buffer.update_keys(up, right, down, left);
+        }
+        assert_eq!(buffer.inputs, Inputs::from([Down, DownRight, Right]));
+
+        // the latest key wins when opposite keys are held
+        let mut buffer = InputBuffer::new(settings, 3);
+        buffer.update_keys(true, false, false, false);
+        buffer.update_keys(true, false, false, false);
+        buffer.update_keys(true, false, false, false);
+        buffer.update_keys(true, false, false, false);
+        buffer.update_keys(true, false, true, false);
+        assert_eq!(buffer.inputs, Inputs::from([Up, Down]));
+
+        // without diagonals keys are pushed as they are pressed
+        let mut buffer = InputBuffer::new(Settings::default(), 3);
+        buffer.update_keys(false, false, true, false);
+        buffer.update_keys(false, true, true, false);
+        assert_eq!(buffer.inputs, Inputs::from([Down, Right]));
+    }
+
+    #[test]
+    fn test_sticks() {
+        let settings = Settings {
+            bounce_threshold: 0.4,
+            right_bounce_threshold: 0.7,
+            ..Settings::default()
+        };
+        // the spring-back after a flick is a bounce on the left stick but not on the right one
+        let flick = [(0.0, 1.0), (0.0, -0.5), (0.0, 0.0)];
+        let mut buffer = InputBuffer::new(settings, 3);
+        for (x, y) in flick {
+            buffer.update_stick(Stick::Left, x, y);
+        }
+        assert_eq!(buffer.inputs, Inputs::from([Up, Down]));
+        let mut buffer = InputBuffer::new(settings, 3);
+        for (x, y) in flick {
+            buffer.update_stick(Stick::Right, x, y);
+        }
+        assert_eq!(buffer.inputs, Inputs::from([Up]));
+        for (x, y) in flick {
+            buffer.update_stick(Stick::Right, x, y);
+        }
+        assert_eq!(buffer.inputs, Inputs::from([Up, Up]));
+
+        // each stick leaves its neutral zone on its own
+        let mut buffer = InputBuffer::new(settings, 3);
+        buffer.update_stick(Stick::Left, 1.0, 0.0);
+        buffer.update_stick(Stick::Right, 1.0, 0.0);
+        assert_eq!(buffer.inputs, Inputs::from([Right, Right]));
+        // the stick that's no longer read doesn't hold the buffer after a switch
+        buffer.update_stick(Stick::Left, 0.0, 1.0);
+        assert_eq!(buffer.inputs, Inputs::from([Right, Right, Up]));
+        buffer.clear();
+        assert!(!buffer.released());
+        buffer.update_stick(Stick::Left, 0.0, 0.0);
+        assert!(buffer.released());
+        // and the new one starts over from the neutral zone
+        buffer.update_stick(Stick::Left, 0.0, 1.0);
+        buffer.update_stick(Stick::Right, 0.0, 1.0);
+        assert_eq!(buffer.inputs, Inputs::from([Up, Up]));
+    }
+
+    fn assert_pos(actual: (f32, f32), expected: (f32, f32)) {
+        let close = |a: f32, b: f32| (a - b).abs() < 1e-4;
+        assert!(
+            close(actual.0, expected.0) && close(actual.1, expected.1),
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn test_deadzones() {
+        let calibrate = |calibration, x, y| StickCalibrator::new(calibration).calibrate(x, y);
+        let square = Calibration {
+            inner_deadzone: 0.2,
+            outer_deadzone: 0.8,
+            ..Calibration::default()
+        };
+        let radial = Calibration {
+            deadzone: DeadzoneShape::Radial,
+            ..square
+        };
+        let scaled = Calibration {
+            deadzone: DeadzoneShape::ScaledRadial,
+            ..square
+        };
+        // the defaults keep the raw positions
+        assert_pos(calibrate(Calibration::default(), 0.3, -0.7), (0.3, -0.7));
+        // square deadzones measure the further axis and radial ones measure the distance
+        assert_pos(calibrate(square, 0.15, 0.15), (0.0, 0.0));
+        assert_pos(calibrate(radial, 0.15, 0.15), (0.2652, 0.2652));
+        assert_pos(calibrate(square, 0.4, 0.0), (0.5, 0.0));
+        assert_pos(calibrate(radial, 0.4, 0.0), (0.5, 0.0));
+        assert_pos(calibrate(scaled, 0.4, 0.0), (0.3333, 0.0));
+        assert_pos(calibrate(scaled, 0.2, 0.0), (0.0, 0.0));
+        // beyond the outer deadzone the stick is pushed all the way
+        assert_pos(calibrate(square, 0.9, -0.3), (1.0, -0.375));
+        assert_pos(calibrate(radial, 0.9, 0.0), (1.0, 0.0));
+        assert_pos(calibrate(scaled, 0.0, -0.95), (0.0, -1.0));
+        // a radial diagonal on the edge of the gate reaches the thresholds of the input buffer
+        let diagonal = calibrate(radial, 0.6, 0.6);
+        assert_pos(diagonal, (1.0, 1.0));
+        let settings = Settings {
+            diagonals: true,
+            ..Settings::default()
+        };
+        let mut buffer = InputBuffer::new(settings, 3);
+        buffer.update_stick(Stick::Left, 0.6, 0.6);
+        assert_eq!(buffer.inputs, Inputs::new());
+        buffer.update_stick(Stick::Left, diagonal.0, diagonal.1);
+        assert_eq!(buffer.inputs, Inputs::from([UpRight]));
+    }
+
+    #[test]
+    fn test_axes() {
+        let calibration = Calibration {
+            invert: [false, true],
+            scale: [1.25, 1.0],
+            ..Calibration::default()
+        };
+        let mut calibrator = StickCalibrator::new(calibration);
+        assert_pos(calibrator.calibrate(0.4, 0.5), (0.5, -0.5));
+        // scaling never pushes the stick beyond the edge
+        assert_pos(calibrator.calibrate(0.9, 0.0), (1.0, 0.0));
+
+        // the range on each side of the offset is stretched back to 1
+        let calibration = Calibration {
+            offset: (0.2, -0.1),
+            ..Calibration::default()
+        };
+        let mut calibrator = StickCalibrator::new(calibration);
+        assert_pos(calibrator.calibrate(0.2, -0.1), (0.0, 0.0));
+        assert_pos(calibrator.calibrate(1.0, 1.0), (1.0, 1.0));
+        assert_pos(calibrator.calibrate(-1.0, -1.0), (-1.0, -1.0));
+        assert_pos(calibrator.calibrate(0.6, -0.55), (0.5, -0.5));
+    }
+
+    #[test]
+    fn test_drift_compensation() {
+        let calibration = Calibration {
+            inner_deadzone: 0.2,
+            drift_compensation: true,
+            ..Calibration::default()
+        };
+        let mut calibrator = StickCalibrator::new(calibration);
+        // a drifting stick is taken as the center after resting inside the inner deadzone for a while
+        for i in 0..60 {
+            let jitter = if i % 2 == 0 { 0.005 } else { -0.005 };
+            calibrator.calibrate(0.15 + jitter, -0.1);
+        }
+        assert_eq!(calibrator.offset(), (0.0, 0.0));
+        calibrator.calibrate(0.15, -0.1);
+        assert_pos(calibrator.offset(), (0.155, -0.1));
+        assert_pos(calibrator.calibrate(0.155, -0.1), (0.0, 0.0));
+        assert_pos(calibrator.calibrate(1.0, 0.0), (1.0, 0.0909));
+
+        // the offset stays when the stick moves
+        for _ in 0..10 {
+            calibrator.calibrate(1.0, 0.0);
+        }
+        assert_pos(calibrator.offset(), (0.155, -0.1));
+
+        // a stick held still far off the center is not drifting
+        let mut calibrator = StickCalibrator::new(calibration);
+        for _ in 0..120 {
+            calibrator.calibrate(0.5, 0.0);
+        }
+        assert_eq!(calibrator.offset(), (0.0, 0.0));
+
+        // a small tilt held outside the inner deadzone is not absorbed as drift
+        let calibration = Calibration {
+            inner_deadzone: 0.1,
+            ..calibration
+        };
+        let mut calibrator = StickCalibrator::new(calibration);
+        for _ in 0..2000 {
+            assert_pos(calibrator.calibrate(0.15, 0.0), (0.15, 0.0));
+        }
+        assert_eq!(calibrator.offset(), (0.0, 0.0));
+
+        // without the inner deadzone, only a small drift is learned
+        let calibration = Calibration {
+            inner_deadzone: 0.0,
+            ..calibration
+        };
+        let mut calibrator = StickCalibrator::new(calibration);
+        for _ in 0..2000 {
+            calibrator.calibrate(0.15, 0.0);
+        }
+        assert_eq!(calibrator.offset(), (0.0, 0.0));
+        for _ in 0..61 {
+            calibrator.calibrate(0.05, 0.0);
+        }
+        assert_pos(calibrator.offset(), (0.05, 0.0));
+
+        // nothing is learned when it's off
+        let mut calibrator = StickCalibrator::new(Calibration::default());
+        for _ in 0..120 {
+            calibrator.calibrate(0.1, 0.0);
+        }
+        assert_eq!(calibrator.offset(), (0.0, 0.0));
+    }
+
     #[test]
     fn bench_inputs() {
         const ROUNDS: usize = 1_000_000;
@@ -0,0 +1,382 @@
+use std::{fmt, str::FromStr};
+
+//----------------------------------------------------------------------------
+//
+//  Keys on keyboards and mice, identified either by their virtual-key codes
+//  or by the scancodes of the physical keys
+//
+//----------------------------------------------------------------------------
+
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Key {
+    /// Virtual-key code, which follows the keyboard layout
+    Virtual(u16),
+    /// Scancode, which follows the physical position of the key regardless of the layout.
+    /// Extended keys are prefixed with `0xE0`
+    Scancode(u16),
+}
+
+impl Key {
+    pub const W: Key = Key::Virtual(b'W' as u16);
+    pub const A: Key = Key::Virtual(b'A' as u16);
+    pub const S: Key = Key::Virtual(b'S' as u16);
+    pub const D: Key = Key::Virtual(b'D' as u16);
+    pub const XBUTTON1: Key = Key::Virtual(0x05);
+    pub const XBUTTON2: Key = Key::Virtual(0x06);
+}
+
+// names of the virtual keys other than letters, digits, function keys and numpad digits
+#[rustfmt::skip]
+const NAMES: &[(&str, u16)] = &[
+    ("LBUTTON", 0x01), ("M1", 0x01),
+    ("RBUTTON", 0x02), ("M2", 0x02),
+    ("MBUTTON", 0x04), ("M3", 0x04),
+    ("XBUTTON1", 0x05), ("M4", 0x05),
+    ("XBUTTON2", 0x06), ("M5", 0x06),
+    ("BACKSPACE", 0x08), ("BACK", 0x08),
+    ("TAB", 0x09),
+    ("ENTER", 0x0D), ("RETURN", 0x0D),
+    ("SHIFT", 0x10),
+    ("CTRL", 0x11), ("CONTROL", 0x11),
+    ("ALT", 0x12), ("MENU", 0x12),
+    ("CAPSLOCK", 0x14), ("CAPITAL", 0x14),
+    ("ESC", 0x1B), ("ESCAPE", 0x1B),
+    ("SPACE", 0x20),
+    ("PAGEUP", 0x21), ("PRIOR", 0x21),
+    ("PAGEDOWN", 0x22), ("NEXT", 0x22),
+    ("END", 0x23),
+    ("HOME", 0x24),
+    ("LEFT", 0x25),
+    ("UP", 0x26),
+    ("RIGHT", 0x27),
+    ("DOWN", 0x28),
+    ("INSERT", 0x2D),
+    ("DELETE", 0x2E),
+    ("MULTIPLY", 0x6A),
+    ("ADD", 0x6B),
+    ("SUBTRACT", 0x6D),
+    ("DECIMAL", 0x6E),
+    ("DIVIDE", 0x6F),
+    ("LSHIFT", 0xA0),
+    ("RSHIFT", 0xA1),
+    ("LCTRL", 0xA2), ("LCONTROL", 0xA2),
+    ("RCTRL", 0xA3), ("RCONTROL", 0xA3),
+    ("LALT", 0xA4), ("LMENU", 0xA4),
+    ("RALT", 0xA5), ("RMENU", 0xA5),
+    (";", 0xBA), ("OEM_1", 0xBA),
+    ("=", 0xBB), ("OEM_PLUS", 0xBB),
+    (",", 0xBC), ("OEM_COMMA", 0xBC),
+    ("-", 0xBD), ("OEM_MINUS", 0xBD),
+    (".", 0xBE), ("OEM_PERIOD", 0xBE),
+    ("/", 0xBF), ("OEM_2", 0xBF),
+    ("`", 0xC0), ("OEM_3", 0xC0),
+    ("[", 0xDB), ("OEM_4", 0xDB),
+    ("\\", 0xDC), ("OEM_5", 0xDC),
+    ("]", 0xDD), ("OEM_6", 0xDD),
+    ("'", 0xDE), ("OEM_7", 0xDE),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownKey;
+
+impl FromStr for Key {
+    type Err = UnknownKey;
+
+    /// Accepts names of keys (`W`, `SPACE`, `LSHIFT`, `M4`, ...) with an optional `VK_` prefix,
+    /// virtual-key codes in hex (`0x57`) and scancodes (`SC:0x11`, `SC:17`). Names are case-insensitive
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.to_ascii_uppercase();
+        if let Some(scancode) = s.strip_prefix("SC:") {
+            let scancode = parse_number(scancode).ok_or(UnknownKey)?;
+            // either a plain scancode or an extended one
+            return match scancode {
+                0x01..=0x7F | 0xE001..=0xE07F => Ok(Key::Scancode(scancode)),
+                _ => Err(UnknownKey),
+            };
+        }
+        if s.starts_with("0X") {
+            let code = parse_number(&s).ok_or(UnknownKey)?;
+            return match code {
+                0x01..=0xFE => Ok(Key::Virtual(code)),
+                _ => Err(UnknownKey),
+            };
+        }
+        let name = s.strip_prefix("VK_").unwrap_or(&s);
+        let code = match name.as_bytes() {
+            // letters and digits share the same codes with ASCII
+            [ch @ (b'A'..=b'Z' | b'0'..=b'9')] => *ch as u16,
+            [b'F', digits @ ..] if !digits.is_empty() => match name[1..].parse::<u16>() {
+                Ok(n @ 1..=24) => 0x70 + n - 1,
+                _ => return Err(UnknownKey),
+            },
+            [b'N', b'U', b'M', b'P', b'A', b'D', digit @ b'0'..=b'9'] | [b'N', b'U', b'M', digit @ b'0'..=b'9'] => {
+                0x60 + (*digit - b'0') as u16
+            }
+            _ => NAMES
+                .iter()
+                .find(|(it, _)| *it == name)
+                .map(|(_, code)| *code)
+                .ok_or(UnknownKey)?,
+        };
+        Ok(Key::Virtual(code))
+    }
+}
+
+fn parse_number(s: &str) -> Option<u16> {
+    match s.strip_prefix("0X") {
+        Some(hex) => u16::from_str_radix(hex, 16).ok(),
+        None => s.parse().ok(),
+    }
+}
+
+impl fmt::Display for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Key::Virtual(code @ (0x30..=0x39 | 0x41..=0x5A)) => write!(f, "{}", code as u8 as char),
+            Key::Virtual(code @ 0x60..=0x69) => write!(f, "NUMPAD{}", code - 0x60),
+            Key::Virtual(code @ 0x70..=0x87) => write!(f, "F{}", code - 0x70 + 1),
+            Key::Virtual(code) => match NAMES.iter().find(|(_, it)| *it == code) {
+                Some((name, _)) => f.write_str(name),
+                None => write!(f, "{code:#04X}"),
+            },
+            Key::Scancode(scancode) => write!(f, "SC:{scancode:#04X}"),
+        }
+    }
+}
+
+impl fmt::Debug for Key {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+//----------------------------------------------------------------------------
+//
+//  Buttons on gamepads, which work alongside keys for some of the slots
+//
+//----------------------------------------------------------------------------
+
+/// Buttons in the layout of Xbox controllers, named after their positions so that they're the same on DualSense
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Button {
+    South,
+    East,
+    North,
+    West,
+    C,
+    Z,
+    L1,
+    L2,
+    R1,
+    R2,
+    Select,
+    Start,
+    Mode,
+    L3,
+    R3,
+    DPadUp,
+    DPadRight,
+    DPadDown,
+    DPadLeft,
+    Paddle1,
+    Paddle2,
+    Paddle3,
+    Paddle4,
+}
+
+// the first name of each button is the canonical one. names are chosen so that they never clash with keys
+#[rustfmt::skip]
+const BUTTON_NAMES: &[(&str, Button)] = &[
+    ("SOUTH", Button::South), ("CROSS", Button::South),
+    ("EAST", Button::East), ("CIRCLE", Button::East),
+    ("NORTH", Button::North), ("TRIANGLE", Button::North),
+    ("WEST", Button::West), ("SQUARE", Button::West),
+    ("BUTTON_C", Button::C),
+    ("BUTTON_Z", Button::Z),
+    ("L1", Button::L1), ("LB", Button::L1),
+    ("L2", Button::L2), ("LT", Button::L2),
+    ("R1", Button::R1), ("RB", Button::R1),
+    ("R2", Button::R2), ("RT", Button::R2),
+    ("SELECT", Button::Select), ("VIEW", Button::Select), ("SHARE", Button::Select), ("CREATE", Button::Select),
+    ("START", Button::Start), ("OPTIONS", Button::Start),
+    ("MODE", Button::Mode), ("GUIDE", Button::Mode), ("PS", Button::Mode),
+    ("L3", Button::L3), ("LS", Button::L3),
+    ("R3", Button::R3), ("RS", Button::R3),
+    ("DPAD_UP", Button::DPadUp),
+    ("DPAD_RIGHT", Button::DPadRight),
+    ("DPAD_DOWN", Button::DPadDown),
+    ("DPAD_LEFT", Button::DPadLeft),
+    ("PADDLE1", Button::Paddle1),
+    ("PADDLE2", Button::Paddle2),
+    ("PADDLE3", Button::Paddle3),
+    ("PADDLE4", Button::Paddle4),
+];
+
+impl Button {
+    #[inline(always)]
+    pub fn is_dpad(self) -> bool {
+        matches!(
+            self,
+            Button::DPadUp | Button::DPadRight | Button::DPadDown | Button::DPadLeft
+        )
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownButton;
+
+impl FromStr for Button {
+    type Err = UnknownButton;
+
+    /// Accepts names of buttons (`R3`, `SELECT`, `DPAD_UP`, ...). Names are case-insensitive
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.to_ascii_uppercase().replace('-', "_");
+        BUTTON_NAMES
+            .iter()
+            .find(|(it, _)| *it == name)
+            .map(|(_, button)| *button)
+            .ok_or(UnknownButton)
+    }
+}
+
+impl fmt::Display for Button {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (name, _) = BUTTON_NAMES.iter().find(|(_, it)| it == self).unwrap();
+        // padded so that buttons line up in tables
+        f.pad(name)
+    }
+}
+
+impl fmt::Debug for Button {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+/// Either a key or a button, for slots that can be triggered by both keyboards and gamepads
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Trigger {
+    Key(Key),
+    Button(Button),
+}
+
+impl FromStr for Trigger {
+    type Err = UnknownKey;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.parse::<Key>() {
+            Ok(key) => Ok(Trigger::Key(key)),
+            Err(_) => s.parse::<Button>().map(Trigger::Button).map_err(|_| UnknownKey),
+        }
+    }
+}
+
+impl fmt::Display for Trigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Trigger::Key(key) => key.fmt(f),
+            Trigger::Button(button) => button.fmt(f),
+        }
+    }
+}
+
+impl fmt::Debug for Trigger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(self, f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use crate::key::{Button, Key, Trigger};
+
+    #[test]
+    fn test_parse() {
+        macro_rules! assert_key {
+            ($name:expr, $key:expr) => {
+                assert_eq!($name.parse::<Key>(), Ok($key), "{}", $name);
+            };
+        }
+
+        macro_rules! assert_unknown {
+            ($name:expr) => {
+                assert!($name.parse::<Key>().is_err(), "{}", $name);
+            };
+        }
+
+        // letters, digits and aliases
+        assert_key!("W", Key::W);
+        assert_key!("w", Key::W);
+        assert_key!("VK_W", Key::W);
+        assert_key!("7", Key::Virtual(0x37));
+        assert_key!("M4", Key::XBUTTON1);
+        assert_key!("xbutton2", Key::XBUTTON2);
+        assert_key!("Space", Key::Virtual(0x20));
+        assert_key!("LShift", Key::Virtual(0xA0));
+        assert_key!("up", Key::Virtual(0x26));
+        assert_key!(";", Key::Virtual(0xBA));
+        // ranges
+        assert_key!("F1", Key::Virtual(0x70));
+        assert_key!("F24", Key::Virtual(0x87));
+        assert_key!("NUMPAD0", Key::Virtual(0x60));
+        assert_key!("NUM9", Key::Virtual(0x69));
+        // codes
+        assert_key!("0x57", Key::W);
+        assert_key!("SC:0x11", Key::Scancode(0x11));
+        assert_key!("SC:17", Key::Scancode(0x11));
+        assert_key!("sc:0xE048", Key::Scancode(0xE048));
+        // illegal
+        assert_unknown!("");
+        assert_unknown!("WW");
+        assert_unknown!("F0");
+        assert_unknown!("F25");
+        assert_unknown!("NUMPAD10");
+        assert_unknown!("0x00");
+        assert_unknown!("0x100");
+        assert_unknown!("0xZZ");
+        assert_unknown!("SC:0");
+        assert_unknown!("SC:0x80");
+        assert_unknown!("SC:");
+        assert_unknown!("VK_");
+    }
+
+    #[test]
+    fn test_display() {
+        for name in [
+            "W",
+            "7",
+            "F12",
+            "NUMPAD3",
+            "SPACE",
+            "M4",
+            "SC:0x11",
+            "SC:0xE048",
+            "0xFE",
+        ] {
+            let key = name.parse::<Key>().unwrap();
+            assert_eq!(key.to_string().parse::<Key>(), Ok(key), "{name}");
+        }
+        assert_eq!(Key::XBUTTON1.to_string(), "XBUTTON1");
+        assert_eq!(Key::Scancode(0x11).to_string(), "SC:0x11");
+    }
+
+    #[test]
+    fn test_buttons() {
+        assert_eq!("R3".parse::<Button>(), Ok(Button::R3));
+        assert_eq!("dpad_up".parse::<Button>(), Ok(Button::DPadUp));
+        assert_eq!("Options".parse::<Button>(), Ok(Button::Start));
+        assert_eq!(Button::South.to_string(), "SOUTH");
+        assert!("R4".parse::<Button>().is_err());
+        assert_eq!("paddle2".parse::<Button>(), Ok(Button::Paddle2));
+        assert!("PADDLE5".parse::<Button>().is_err());
+        // keys come first
+        assert_eq!("M4".parse::<Trigger>(), Ok(Trigger::Key(Key::XBUTTON1)));
+        assert_eq!("select".parse::<Trigger>(), Ok(Trigger::Button(Button::Select)));
+        assert_eq!("RB".parse::<Trigger>(), Ok(Trigger::Button(Button::R1)));
+        assert!("SELECTT".parse::<Trigger>().is_err());
+        for (name, button) in super::BUTTON_NAMES {
+            assert!(name.parse::<Key>().is_err(), "{name}");
+            assert_eq!(button.to_string().parse::<Button>(), Ok(*button));
+        }
+    }
+}
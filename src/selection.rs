@@ -0,0 +1,221 @@
+use std::collections::HashMap;
+
+use crate::config::{Binding, ToolPolicy, UID};
+
+/// What the selection needs to know about the prosthetic tools of the player
+pub trait Inventory {
+    /// Whether the tool is in one of the prosthetic slots
+    fn is_equipped(&self, tool: UID) -> bool;
+    /// Whether the tool was taken out of a slot by the MOD and is waiting to be put back
+    fn is_ejected(&self, tool: UID) -> bool;
+    /// Whether the player has obtained the tool
+    fn owns(&self, tool: UID) -> bool;
+}
+
+/// Picks one of the prosthetic tools bound to the same input by the policy of the binding,
+/// and remembers the uses that `cycle` and `least-recently-used` depend on
+#[derive(Default)]
+pub struct ToolSelection {
+    // the tool last used by each binding
+    turns: HashMap<Binding, UID>,
+    // when each tool is used last time. tools never used are missing
+    uses: HashMap<UID, u64>,
+    clock: u64,
+    // the tool picked this frame and its binding, which is what a use goes to
+    picked: Option<(Binding, UID)>,
+}
+
+impl ToolSelection {
+    /// The tool to switch to. `None` when none of the tools is owned
+    pub fn pick(&self, binding: Binding, policy: ToolPolicy, tools: &[UID], inventory: &impl Inventory) -> Option<UID> {
+        let mut owned = tools.iter().copied().filter(|it| inventory.owns(*it));
+        match policy {
+            ToolPolicy::EquippedFirst => {
+                let equipped = tools.iter().copied().find(|it| inventory.is_equipped(*it));
+                let ejected = || tools.iter().copied().find(|it| inventory.is_ejected(*it));
+                equipped.or_else(ejected).or_else(|| owned.next())
+            }
+            ToolPolicy::StrictOrder => owned.next(),
+            ToolPolicy::Cycle => {
+                // starts over from the first one when the last used one is no longer bound
+                let last = self.turns.get(&binding);
+                let start = last
+                    .and_then(|last| tools.iter().position(|it| it == last))
+                    .map_or(0, |it| it + 1);
+                let mut tools = tools.iter().copied().cycle().skip(start).take(tools.len());
+                tools.find(|it| inventory.owns(*it))
+            }
+            // ties are broken by the order in the config
+            ToolPolicy::LeastRecentlyUsed => owned.min_by_key(|it| self.uses.get(it).copied().unwrap_or_default()),
+        }
+    }
+
+    /// Remembers that the tool picked for the binding is used
+    pub fn record_use(&mut self, binding: Binding, tool: UID) {
+        self.clock += 1;
+        self.uses.insert(tool, self.clock);
+        self.turns.insert(binding, tool);
+    }
+
+    /// Remembers the tool picked for the binding this frame. `None` when no tool is picked
+    pub fn set_picked(&mut self, picked: Option<(Binding, UID)>) {
+        self.picked = picked;
+    }
+
+    /// Remembers that the picked tool is used and returns it. Nothing moves on when no tool is picked
+    pub fn use_picked(&mut self) -> Option<UID> {
+        let (binding, tool) = self.picked?;
+        self.record_use(binding, tool);
+        Some(tool)
+    }
+
+    /// Forgets every use so that all the bindings start over
+    pub fn reset(&mut self) {
+        *self = ToolSelection::default();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::{
+        config::{Binding, ToolPolicy, UID},
+        input::Input::*,
+        selection::{Inventory, ToolSelection},
+    };
+
+    #[derive(Default)]
+    struct MockInventory {
+        equipped: HashSet<UID>,
+        ejected: Option<UID>,
+        owned: HashSet<UID>,
+    }
+
+    impl MockInventory {
+        fn new(equipped: impl IntoIterator<Item = UID>, owned: impl IntoIterator<Item = UID>) -> MockInventory {
+            let equipped = equipped.into_iter().collect::<HashSet<_>>();
+            let owned = owned.into_iter().chain(equipped.iter().copied()).collect();
+            MockInventory {
+                equipped,
+                owned,
+                ..Default::default()
+            }
+        }
+    }
+
+    impl Inventory for MockInventory {
+        fn is_equipped(&self, tool: UID) -> bool {
+            self.equipped.contains(&tool)
+        }
+
+        fn is_ejected(&self, tool: UID) -> bool {
+            self.ejected == Some(tool)
+        }
+
+        fn owns(&self, tool: UID) -> bool {
+            self.owned.contains(&tool) || self.is_ejected(tool)
+        }
+    }
+
+    const SPEARS: [UID; 3] = [78400, 78300, 78000];
+
+    #[test]
+    fn test_equipped_first() {
+        let selection = ToolSelection::default();
+        let binding = Binding::Motion([Up, Up].into());
+        let pick = |inventory: &MockInventory| selection.pick(binding, ToolPolicy::EquippedFirst, &SPEARS, inventory);
+        // the equipped one wins over the ones listed before it
+        assert_eq!(pick(&MockInventory::new([78000], [78400, 78300])), Some(78000));
+        assert_eq!(pick(&MockInventory::new([78300, 78000], [])), Some(78300));
+        // then the ejected one
+        let mut inventory = MockInventory::new([], [78400]);
+        inventory.ejected = Some(78300);
+        assert_eq!(pick(&inventory), Some(78300));
+        // then the first owned one
+        assert_eq!(pick(&MockInventory::new([70000], [78300, 78000])), Some(78300));
+        assert_eq!(pick(&MockInventory::new([70000], [])), None);
+    }
+
+    #[test]
+    fn test_strict_order() {
+        let selection = ToolSelection::default();
+        let pick = |inventory: &MockInventory| selection.pick(Binding::X1, ToolPolicy::StrictOrder, &SPEARS, inventory);
+        assert_eq!(pick(&MockInventory::new([78000], [78400])), Some(78400));
+        assert_eq!(pick(&MockInventory::new([78000], [78300])), Some(78300));
+        assert_eq!(pick(&MockInventory::new([78000], [])), Some(78000));
+        assert_eq!(pick(&MockInventory::new([], [])), None);
+    }
+
+    #[test]
+    fn test_cycle() {
+        let mut selection = ToolSelection::default();
+        let binding = Binding::Block;
+        let inventory = MockInventory::new([78000], [78400, 78300]);
+        let mut picks = Vec::new();
+        for _ in 0..4 {
+            let tool = selection.pick(binding, ToolPolicy::Cycle, &SPEARS, &inventory).unwrap();
+            // picking alone doesn't move on
+            assert_eq!(
+                selection.pick(binding, ToolPolicy::Cycle, &SPEARS, &inventory),
+                Some(tool)
+            );
+            selection.record_use(binding, tool);
+            picks.push(tool);
+        }
+        assert_eq!(picks, [78400, 78300, 78000, 78400]);
+
+        // the ones not owned are skipped
+        let inventory = MockInventory::new([], [78400, 78000]);
+        assert_eq!(
+            selection.pick(binding, ToolPolicy::Cycle, &SPEARS, &inventory),
+            Some(78000)
+        );
+        // other bindings take turns on their own
+        assert_eq!(
+            selection.pick(Binding::X1, ToolPolicy::Cycle, &SPEARS, &inventory),
+            Some(78400)
+        );
+
+        selection.reset();
+        assert_eq!(
+            selection.pick(binding, ToolPolicy::Cycle, &SPEARS, &inventory),
+            Some(78400)
+        );
+    }
+
+    #[test]
+    fn test_least_recently_used() {
+        let mut selection = ToolSelection::default();
+        let binding = Binding::X2;
+        let inventory = MockInventory::new([78300], [78400, 78000]);
+        let pick =
+            |selection: &ToolSelection| selection.pick(binding, ToolPolicy::LeastRecentlyUsed, &SPEARS, &inventory);
+        // the ones never used come first in order
+        assert_eq!(pick(&selection), Some(78400));
+        selection.record_use(binding, 78400);
+        assert_eq!(pick(&selection), Some(78300));
+        // uses through other bindings count as well
+        selection.record_use(Binding::X1, 78300);
+        assert_eq!(pick(&selection), Some(78000));
+        selection.record_use(binding, 78000);
+        assert_eq!(pick(&selection), Some(78400));
+        selection.record_use(binding, 78400);
+        assert_eq!(pick(&selection), Some(78300));
+    }
+
+    #[test]
+    fn test_picked() {
+        let mut selection = ToolSelection::default();
+        let binding = Binding::Motion([Up, Up].into());
+        let inventory = MockInventory::new([], SPEARS);
+        let pick = |selection: &ToolSelection| selection.pick(binding, ToolPolicy::Cycle, &SPEARS, &inventory);
+        selection.set_picked(pick(&selection).map(|tool| (binding, tool)));
+        assert_eq!(selection.use_picked(), Some(78400));
+        assert_eq!(pick(&selection), Some(78300));
+        // a use without any bound tool doesn't move the last picked binding on
+        selection.set_picked(None);
+        assert_eq!(selection.use_picked(), None);
+        assert_eq!(pick(&selection), Some(78300));
+    }
+}
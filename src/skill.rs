@@ -0,0 +1,289 @@
+use std::fmt;
+
+use crate::config::UID;
+
+//----------------------------------------------------------------------------
+//
+//  A catalogue of every combat art and prosthetic tool in the game
+//
+//----------------------------------------------------------------------------
+
+// UIDs of the combat arts
+pub const WHIRLWIND_SLASH: UID = 5100;
+pub const NIGHTJAR_SLASH: UID = 5200;
+pub const ICHIMONJI: UID = 5300;
+pub const DRAGON_FLASH: UID = 5400;
+pub const ASHINA_CROSS: UID = 5500;
+pub const FLOATING_PASSAGE: UID = 5600;
+pub const MORTAL_DRAW: UID = 5700;
+pub const SENPOU_LEAPING_KICKS: UID = 5800;
+pub const PRAYING_STRIKES: UID = 5900;
+pub const SHADOWRUSH: UID = 6000;
+pub const ONE_MIND: UID = 6100;
+pub const NIGHTJAR_SLASH_REVERSAL: UID = 7000;
+pub const ICHIMONJI_DOUBLE: UID = 7100;
+pub const SPIRAL_CLOUD_PASSAGE: UID = 7200;
+pub const EMPOWERED_MORTAL_DRAW: UID = 7300;
+pub const HIGH_MONK: UID = 7400;
+pub const PRAYING_STRIKES_EXORCISM: UID = 7500;
+pub const SHADOWFALL: UID = 7600;
+pub const SAKURA_DANCE: UID = 7700;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Category {
+    CombatArt,
+    Shuriken,
+    FlameVent,
+    Firecracker,
+    Axe,
+    Umbrella,
+    Sabimaru,
+    MistRaven,
+    DivineAbduction,
+    Spear,
+    Whistle,
+}
+
+impl Category {
+    pub fn is_prosthetic_tool(self) -> bool {
+        self != Category::CombatArt
+    }
+}
+
+impl fmt::Display for Category {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Category::CombatArt => "combat art",
+            Category::Shuriken => "shuriken",
+            Category::FlameVent => "flame vent",
+            Category::Firecracker => "firecracker",
+            Category::Axe => "axe",
+            Category::Umbrella => "umbrella",
+            Category::Sabimaru => "sabimaru",
+            Category::MistRaven => "mist raven",
+            Category::DivineAbduction => "divine abduction",
+            Category::Spear => "spear",
+            Category::Whistle => "whistle",
+        };
+        f.write_str(name)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Skill {
+    pub uid: UID,
+    pub en: &'static str,
+    pub zh: &'static str,
+    pub category: Category,
+    /// The UID of the most basic skill that this one is upgraded from. It's the skill's own UID if it's not an upgrade
+    pub family: UID,
+}
+
+impl Skill {
+    pub fn find(uid: UID) -> Option<&'static Skill> {
+        SKILLS.iter().find(|it| it.uid == uid)
+    }
+
+    /// Finds the skill by its English or Chinese name, ignoring cases, whitespace and punctuations
+    pub fn find_by_name(name: &str) -> Option<&'static Skill> {
+        let name = normalize(name);
+        SKILLS
+            .iter()
+            .find(|it| normalize(it.en) == name || normalize(it.zh) == name)
+    }
+
+    /// The name that's the closest to the given one, as long as it's close enough to be a typo
+    pub fn suggest(name: &str) -> Option<&'static str> {
+        let name = normalize(name).chars().collect::<Vec<_>>();
+        SKILLS
+            .iter()
+            .flat_map(|it| [it.en, it.zh])
+            .filter_map(|candidate| {
+                let normalized = normalize(candidate).chars().collect::<Vec<_>>();
+                let distance = distance(&name, &normalized);
+                // roughly 1 typo for every 3 characters
+                (distance <= usize::max(normalized.len() / 3, 1)).then_some((distance, candidate))
+            })
+            .min_by_key(|(distance, _)| *distance)
+            .map(|(_, candidate)| candidate)
+    }
+
+    /// Skills of the same upgrade family, from the most basic one to the most advanced one
+    pub fn family(&self) -> impl Iterator<Item = &'static Skill> + use<> {
+        let family = self.family;
+        SKILLS.iter().filter(move |it| it.family == family)
+    }
+}
+
+/// Describes the UID with the name of the skill for logs and diagnostics, e.g., `Shadowfall (7600)`
+pub fn describe(uid: UID) -> impl fmt::Display {
+    struct Describe(UID);
+    impl fmt::Display for Describe {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match Skill::find(self.0) {
+                Some(skill) => write!(f, "{} ({})", skill.en, skill.uid),
+                None => write!(f, "{}", self.0),
+            }
+        }
+    }
+    Describe(uid)
+}
+
+fn normalize(name: &str) -> String {
+    name.chars()
+        .filter(|ch| ch.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+// levenshtein distance
+fn distance(a: &[char], b: &[char]) -> usize {
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+    for (i, a) in a.iter().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, b) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(a != b);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(row[j + 1] + 1);
+        }
+    }
+    row[b.len()]
+}
+
+macro_rules! skills {
+    ($($uid:expr, $en:expr, $zh:expr, $category:ident, $family:expr;)*) => {
+        pub const SKILLS: &[Skill] = &[$(
+            Skill { uid: $uid, en: $en, zh: $zh, category: Category::$category, family: $family },
+        )*];
+    };
+}
+
+// upgrades come after their families. Nightjar Slash Reversal is learned after Nightjar Slash but slashes
+// backwards, so it's a family of its own rather than falling back to a slash the other way
+skills! {
+    WHIRLWIND_SLASH,          "Whirlwind Slash",            "旋风斩",           CombatArt,       WHIRLWIND_SLASH;
+    NIGHTJAR_SLASH,           "Nightjar Slash",             "寄鹰斩",           CombatArt,       NIGHTJAR_SLASH;
+    NIGHTJAR_SLASH_REVERSAL,  "Nightjar Slash Reversal",    "寄鹰斩·反向回旋",  CombatArt,       NIGHTJAR_SLASH_REVERSAL;
+    ICHIMONJI,                "Ichimonji",                  "一字斩",           CombatArt,       ICHIMONJI;
+    ICHIMONJI_DOUBLE,         "Ichimonji: Double",          "一字斩·二连",      CombatArt,       ICHIMONJI;
+    DRAGON_FLASH,             "Dragon Flash",               "秘传·龙闪",        CombatArt,       DRAGON_FLASH;
+    ASHINA_CROSS,             "Ashina Cross",               "绝技·苇名十字斩",  CombatArt,       ASHINA_CROSS;
+    FLOATING_PASSAGE,         "Floating Passage",           "绝技·飞渡浮舟",    CombatArt,       FLOATING_PASSAGE;
+    SPIRAL_CLOUD_PASSAGE,     "Spiral Cloud Passage",       "秘传·飞渡漩涡云",  CombatArt,       SPIRAL_CLOUD_PASSAGE;
+    MORTAL_DRAW,              "Mortal Draw",                "绝技·不死斩",      CombatArt,       MORTAL_DRAW;
+    EMPOWERED_MORTAL_DRAW,    "Empowered Mortal Draw",      "秘传·不死斩",      CombatArt,       MORTAL_DRAW;
+    SENPOU_LEAPING_KICKS,     "Senpou Leaping Kicks",       "仙峰脚",           CombatArt,       SENPOU_LEAPING_KICKS;
+    HIGH_MONK,                "High Monk",                  "绝技·仙峰寺菩萨脚", CombatArt,      SENPOU_LEAPING_KICKS;
+    PRAYING_STRIKES,          "Praying Strikes",            "连击叩拜拳",       CombatArt,       PRAYING_STRIKES;
+    PRAYING_STRIKES_EXORCISM, "Praying Strikes - Exorcism", "连击叩拜拳·破魔",  CombatArt,       PRAYING_STRIKES;
+    SHADOWRUSH,               "Shadowrush",                 "绝技·巨型忍者突刺", CombatArt,      SHADOWRUSH;
+    SHADOWFALL,               "Shadowfall",                 "秘传·巨型忍者落杀", CombatArt,      SHADOWRUSH;
+    ONE_MIND,                 "One Mind",                   "秘传·一心",        CombatArt,       ONE_MIND;
+    SAKURA_DANCE,             "Sakura Dance",               "秘传·樱舞",        CombatArt,       SAKURA_DANCE;
+    70000,                    "Loaded Shuriken",            "手里剑",           Shuriken,        70000;
+    70100,                    "Spinning Shuriken",          "陀螺手里剑",       Shuriken,        70000;
+    70200,                    "Gouging Top",                "穿刺陀螺",         Shuriken,        70000;
+    70300,                    "Phantom Kunai",              "幻影苦无",         Shuriken,        70000;
+    70400,                    "Sen Throw",                  "金钱镖",           Shuriken,        70000;
+    70500,                    "Lazulite Shuriken",          "琉璃手里剑",       Shuriken,        70000;
+    71000,                    "Shinobi Firecracker",        "爆竹",             Firecracker,     71000;
+    71100,                    "Spring-load Firecracker",    "弹簧式爆竹",       Firecracker,     71000;
+    71200,                    "Long Spark",                 "长效火花",         Firecracker,     71000;
+    71300,                    "Purple Fume Spark",          "紫烟火花",         Firecracker,     71000;
+    72000,                    "Flame Vent",                 "吹火筒",           FlameVent,       72000;
+    72100,                    "Spring-load Flame Vent",     "弹簧吹火筒",       FlameVent,       72000;
+    72200,                    "Okinaga's Flame Vent",       "气吹长火筒",       FlameVent,       72000;
+    72300,                    "Lazulite Sacred Flame",      "琉璃之净火",       FlameVent,       72000;
+    73000,                    "Loaded Axe",                 "机关斧",           Axe,             73000;
+    73100,                    "Spring-load Axe",            "弹簧式机关斧",     Axe,             73000;
+    73200,                    "Sparking Axe",               "火镰式机关斧",     Axe,             73000;
+    73300,                    "Lazulite Axe",               "琉璃斧",           Axe,             73000;
+    74000,                    "Mist Raven",                 "雾鸦",             MistRaven,       74000;
+    74100,                    "Aged Feather Mist Raven",    "老羽雾鸦",         MistRaven,       74000;
+    74200,                    "Great Feather Mist Raven",   "神羽雾鸦",         MistRaven,       74000;
+    75000,                    "Sabimaru",                   "锈丸",             Sabimaru,        75000;
+    75100,                    "Improved Sabimaru",          "表里锈丸",         Sabimaru,        75000;
+    75200,                    "Piercing Sabimaru",          "穿刺锈丸",         Sabimaru,        75000;
+    75300,                    "Lazulite Sabimaru",          "琉璃锈丸",         Sabimaru,        75000;
+    76000,                    "Loaded Umbrella",            "机关伞",           Umbrella,        76000;
+    76100,                    "Loaded Umbrella - Magnet",   "磁铁轴机关伞",     Umbrella,        76000;
+    76200,                    "Suzaku's Lotus Umbrella",    "朱雀红莲伞",       Umbrella,        76000;
+    76300,                    "Phoenix's Lilac Umbrella",   "凤凰紫青伞",       Umbrella,        76000;
+    77000,                    "Divine Abduction",           "神隐",             DivineAbduction, 77000;
+    77100,                    "Double Divine Abduction",    "二度神隐",         DivineAbduction, 77000;
+    77200,                    "Golden Vortex",              "捐赠簿",           DivineAbduction, 77000;
+    78000,                    "Loaded Spear",               "机关长枪",         Spear,           78000;
+    78100,                    "Loaded Spear - Thrust Type", "突刺式机关长枪",   Spear,           78000;
+    78200,                    "Loaded Spear - Cleave Type", "回旋式机关长枪",   Spear,           78000;
+    78300,                    "Spiral Spear",               "螺旋号",           Spear,           78000;
+    78400,                    "Leaping Flame",              "火舌",             Spear,           78000;
+    79000,                    "Finger Whistle",             "口哨",             Whistle,         79000;
+    79100,                    "Mountain Echo",              "回声口哨",         Whistle,         79000;
+    79200,                    "Malcontent",                 "爱哭鬼",           Whistle,         79000;
+}
+
+#[cfg(test)]
+mod test {
+    use std::collections::HashSet;
+
+    use crate::skill::{Category, SKILLS, Skill, describe, distance, normalize};
+
+    #[test]
+    fn test_names() {
+        // names never collide
+        let names = SKILLS
+            .iter()
+            .flat_map(|it| [normalize(it.en), normalize(it.zh)])
+            .collect::<HashSet<_>>();
+        assert_eq!(names.len(), SKILLS.len() * 2);
+
+        let find = |name| Skill::find_by_name(name).map(|it| it.uid);
+        assert_eq!(find("Shadowfall"), Some(7600));
+        assert_eq!(find("SHADOWFALL"), Some(7600));
+        assert_eq!(find("Spiral Spear"), Some(78300));
+        assert_eq!(find("spiral-spear"), Some(78300));
+        assert_eq!(find("Suzakus Lotus Umbrella"), Some(76200));
+        assert_eq!(find("Ichimonji: Double"), Some(7100));
+        assert_eq!(find("一字斩·二连"), Some(7100));
+        assert_eq!(find("一字斩二连"), Some(7100));
+        assert_eq!(find("Shadowfal"), None);
+
+        assert_eq!(Skill::suggest("Shadowfal"), Some("Shadowfall"));
+        assert_eq!(Skill::suggest("Spiral Spaer"), Some("Spiral Spear"));
+        assert_eq!(Skill::suggest("一字斩二"), Some("一字斩"));
+        assert_eq!(Skill::suggest("Nothing Like It"), None);
+
+        assert_eq!(distance(&['a', 'b', 'c'], &['a', 'c']), 1);
+        assert_eq!(distance(&['a', 'b'], &['b', 'a']), 2);
+        assert_eq!(distance(&[], &['a', 'b']), 2);
+    }
+
+    #[test]
+    fn test_catalogue() {
+        let uids = SKILLS.iter().map(|it| it.uid).collect::<HashSet<_>>();
+        assert_eq!(uids.len(), SKILLS.len());
+        assert_eq!(
+            SKILLS.iter().filter(|it| it.category == Category::CombatArt).count(),
+            19
+        );
+        assert_eq!(SKILLS.iter().filter(|it| it.category.is_prosthetic_tool()).count(), 40);
+        for skill in SKILLS {
+            // the family is a skill of the same category that appears no later than the skill itself
+            let family = Skill::find(skill.family).unwrap();
+            assert_eq!(family.category, skill.category, "{}", skill.en);
+            assert_eq!(family.family, family.uid, "{}", skill.en);
+            assert!(skill.family().any(|it| it.uid == skill.uid));
+        }
+
+        assert_eq!(Skill::find(7600).map(|it| it.en), Some("Shadowfall"));
+        assert_eq!(Skill::find(7650), None);
+        assert_eq!(
+            Skill::find(7100).unwrap().family().map(|it| it.uid).collect::<Vec<_>>(),
+            [5300, 7100]
+        );
+        assert_eq!(Skill::find(7000).unwrap().family().count(), 1);
+        assert_eq!(describe(7600).to_string(), "Shadowfall (7600)");
+        assert_eq!(describe(7650).to_string(), "7650");
+    }
+}